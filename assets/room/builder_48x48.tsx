@@ -0,0 +1,10 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.8" tiledversion="1.8.2" name="builder_48x48" tilewidth="48" tileheight="48" tilecount="8284" columns="76">
+ <image source="builder_48x48.png" width="3648" height="5232"/>
+ <tile id="1786" type="carpet"/>
+ <tile id="1787" type="carpet"/>
+ <tile id="1788" type="carpet"/>
+ <tile id="1862" type="carpet"/>
+ <tile id="1863" type="carpet"/>
+ <tile id="1864" type="carpet"/>
+</tileset>
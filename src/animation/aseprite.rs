@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context};
+use bevy::{
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    prelude::*,
+    sprite::Rect,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::animation::clip;
+
+/// Loads `*.aseprite.json` sprite sheets, with their tags as the `clips` labeled asset.
+#[derive(Default)]
+pub(crate) struct Loader;
+
+#[derive(Deserialize)]
+struct Export {
+    frames: Frames,
+    meta: Meta,
+}
+
+/// Aseprite exports frames either as an array or as a map keyed by file name.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Frames {
+    Array(Vec<Frame>),
+    Hash(serde_json::Map<String, serde_json::Value>),
+}
+
+#[derive(Deserialize)]
+struct Frame {
+    frame: Bounds,
+    duration: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Meta {
+    image: String,
+    size: Size,
+    #[serde(default)]
+    frame_tags: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Size {
+    w: f32,
+    h: f32,
+}
+
+#[derive(Deserialize)]
+struct Bounds {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+    from: usize,
+    to: usize,
+    direction: String,
+    repeat: Option<String>,
+}
+
+impl Frames {
+    fn into_vec(self) -> serde_json::Result<Vec<Frame>> {
+        match self {
+            Frames::Array(frames) => Ok(frames),
+            Frames::Hash(frames) => frames
+                .into_iter()
+                .map(|(_, frame)| serde_json::from_value(frame))
+                .collect(),
+        }
+    }
+}
+
+impl Bounds {
+    fn rect(&self) -> Rect {
+        Rect {
+            min: Vec2::new(self.x, self.y),
+            max: Vec2::new(self.x + self.w, self.y + self.h),
+        }
+    }
+}
+
+impl Tag {
+    fn clip(&self, frames: &[Frame]) -> clip::Clip {
+        let durations = frames[self.from..=self.to]
+            .iter()
+            .map(|frame| frame.duration)
+            .collect::<Vec<_>>();
+        let frame_time = durations[0];
+
+        let (playback, reverse) = match self.direction.as_str() {
+            "reverse" => (clip::Playback::Loop, true),
+            "pingpong" => (clip::Playback::PingPong, false),
+            "pingpong_reverse" => (clip::Playback::PingPong, true),
+            _ => (clip::Playback::Loop, false),
+        };
+
+        // Aseprite stops on the last frame when a tag has a finite repeat count.
+        let playback = match self.repeat.as_deref() {
+            Some(repeat) if repeat != "0" && playback == clip::Playback::Loop => {
+                clip::Playback::Once
+            }
+            _ => playback,
+        };
+
+        clip::Clip {
+            start: self.from,
+            length: durations.len(),
+            frame_time: milliseconds(frame_time),
+            playback,
+            reverse,
+            frames: durations
+                .into_iter()
+                .enumerate()
+                .filter(|(_, duration)| *duration != frame_time)
+                .map(|(offset, duration)| {
+                    (
+                        offset,
+                        clip::Frame {
+                            duration: Some(milliseconds(duration)),
+                            events: Vec::new(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl AssetLoader for Loader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let export: Export = serde_json::from_slice(bytes)?;
+            let frames = export.frames.into_vec()?;
+            if let Some(index) = frames.iter().position(|frame| frame.duration == 0) {
+                bail!("frame {index} lasts no time");
+            }
+
+            let image_path = load_context
+                .path()
+                .parent()
+                .map_or_else(Default::default, std::path::Path::to_path_buf)
+                .join(&export.meta.image);
+            let texture = load_context.get_handle(AssetPath::new_ref(&image_path, None));
+
+            let mut atlas =
+                TextureAtlas::new_empty(texture, Vec2::new(export.meta.size.w, export.meta.size.h));
+            for frame in &frames {
+                atlas.add_texture(frame.frame.rect());
+            }
+
+            let mut clips = HashMap::new();
+            for tag in &export.meta.frame_tags {
+                if tag.from > tag.to || tag.to >= frames.len() {
+                    bail!("tag {:?} runs past the last frame", tag.name);
+                }
+
+                let clip = tag.clip(&frames);
+                clip.validate()
+                    .with_context(|| format!("in tag {:?}", tag.name))?;
+                clips.insert(tag.name.clone(), clip);
+            }
+
+            load_context.set_labeled_asset("clips", LoadedAsset::new(clip::Clips(clips)));
+            load_context.set_default_asset(
+                LoadedAsset::new(atlas).with_dependency(AssetPath::new(image_path, None)),
+            );
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json"]
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn milliseconds(duration: u32) -> f32 {
+    duration as f32 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(durations: &[u32]) -> Vec<Frame> {
+        durations
+            .iter()
+            .map(|&duration| Frame {
+                frame: Bounds {
+                    x: 0.0,
+                    y: 0.0,
+                    w: 48.0,
+                    h: 48.0,
+                },
+                duration,
+            })
+            .collect()
+    }
+
+    fn tag(direction: &str, repeat: Option<&str>) -> Tag {
+        Tag {
+            name: "tag".to_string(),
+            from: 1,
+            to: 3,
+            direction: direction.to_string(),
+            repeat: repeat.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn mirror_export_parses() {
+        let export: Export =
+            serde_json::from_str(include_str!("../../assets/mirror/01_48x48.aseprite.json"))
+                .unwrap();
+
+        assert!(!export.frames.into_vec().unwrap().is_empty());
+    }
+
+    #[test]
+    fn tag_keeps_frame_durations() {
+        let clip = tag("forward", None).clip(&frames(&[50, 100, 100, 300]));
+
+        assert_eq!((clip.start, clip.length), (1, 3));
+        assert!((clip.duration(0) - 0.1).abs() < f32::EPSILON);
+        assert!((clip.duration(2) - 0.3).abs() < f32::EPSILON);
+        assert!(clip.validate().is_ok());
+    }
+
+    #[test]
+    fn tag_direction_and_repeat_pick_playback() {
+        let frames = frames(&[100; 4]);
+
+        let clip = tag("pingpong_reverse", None).clip(&frames);
+        assert!(clip.playback == clip::Playback::PingPong && clip.reverse);
+
+        let clip = tag("reverse", Some("1")).clip(&frames);
+        assert!(clip.playback == clip::Playback::Once && clip.reverse);
+
+        let clip = tag("forward", Some("0")).clip(&frames);
+        assert!(clip.playback == clip::Playback::Loop && !clip.reverse);
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Context};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+/// Named animation clips of a sprite sheet, loaded from `*.clips.ron` files.
+#[derive(Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "5c1a3a0e-8f3e-4a57-9d0b-2f6b1e0c7a41"]
+pub(crate) struct Clips(pub(crate) HashMap<String, Clip>);
+
+#[derive(Deserialize)]
+pub(crate) struct Clip {
+    pub(crate) start: usize,
+    pub(crate) length: usize,
+    pub(crate) frame_time: f32,
+    #[serde(default)]
+    pub(crate) playback: Playback,
+    #[serde(default)]
+    pub(crate) reverse: bool,
+    /// Overrides for individual frames, keyed by their offset from `start`.
+    #[serde(default)]
+    pub(crate) frames: HashMap<usize, Frame>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Frame {
+    pub(crate) duration: Option<f32>,
+    #[serde(default)]
+    pub(crate) events: Vec<String>,
+}
+
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) enum Playback {
+    #[default]
+    Loop,
+    PingPong,
+    /// Stops on the last frame.
+    #[serde(alias = "HoldLastFrame")]
+    Once,
+}
+
+#[derive(Default)]
+pub(crate) struct Loader;
+
+impl Clips {
+    pub(crate) fn get(&self, name: &str) -> Option<&Clip> {
+        self.0.get(name)
+    }
+}
+
+impl Clip {
+    pub(crate) fn duration(&self, offset: usize) -> f32 {
+        self.frames
+            .get(&offset)
+            .and_then(|frame| frame.duration)
+            .unwrap_or(self.frame_time)
+    }
+
+    pub(crate) fn events(&self, offset: usize) -> &[String] {
+        self.frames
+            .get(&offset)
+            .map_or(&[], |frame| frame.events.as_slice())
+    }
+
+    /// Rejects clips that could never be shown, such as frames lasting no time at all.
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if self.length == 0 {
+            bail!("a clip needs at least one frame");
+        }
+
+        for offset in 0..self.length {
+            let duration = self.duration(offset);
+            if !(duration.is_finite() && duration > 0.0) {
+                bail!("frame {offset} lasts {duration} seconds");
+            }
+        }
+
+        if let Some(offset) = self.frames.keys().find(|&&offset| offset >= self.length) {
+            bail!("frame {offset} is past the end of the clip");
+        }
+
+        Ok(())
+    }
+}
+
+impl AssetLoader for Loader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let clips: Clips = ron::de::from_bytes(bytes)?;
+            for (name, clip) in &clips.0 {
+                clip.validate()
+                    .with_context(|| format!("in clip {name:?}"))?;
+            }
+            load_context.set_default_asset(LoadedAsset::new(clips));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["clips.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clips(ron: &str) -> anyhow::Result<()> {
+        let clips: Clips = ron::de::from_str(ron)?;
+        clips.0.values().try_for_each(Clip::validate)
+    }
+
+    #[test]
+    fn character_clips_are_valid() {
+        clips(include_str!("../../assets/character/04_48x48.clips.ron")).unwrap();
+    }
+
+    #[test]
+    fn rejects_frames_that_last_no_time() {
+        assert!(clips(r#"{"a": (start: 0, length: 2, frame_time: 0.0)}"#).is_err());
+        assert!(clips(r#"{"a": (start: 0, length: 2, frame_time: -0.1)}"#).is_err());
+        assert!(clips(
+            r#"{"a": (start: 0, length: 2, frame_time: 0.1, frames: {1: (duration: Some(0.0))})}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn rejects_empty_clips_and_frames_past_the_end() {
+        assert!(clips(r#"{"a": (start: 0, length: 0, frame_time: 0.1)}"#).is_err());
+        assert!(clips(
+            r#"{"a": (start: 0, length: 2, frame_time: 0.1, frames: {2: (events: [])})}"#
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,406 @@
+pub(crate) mod aseprite;
+pub(crate) mod clip;
+pub(crate) mod state_machine;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// The shortest a frame is shown for, so a very high speed cannot stall the timer.
+const MIN_FRAME_TIME: f32 = 0.001;
+/// The slowest a clip plays, so a speed of zero cannot make a frame last forever.
+const MIN_SPEED: f32 = 0.01;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<clip::Clips>()
+            .add_event::<AnimationEvent>()
+            .add_event::<AnimationFinished>()
+            .init_asset_loader::<aseprite::Loader>()
+            .init_asset_loader::<clip::Loader>()
+            .add_system(state_machine::transition.label(AnimationSystem::Transition))
+            .add_system(
+                play.label(AnimationSystem::Play)
+                    .after(AnimationSystem::Transition),
+            )
+            .add_system(animate.after(AnimationSystem::Play));
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<AnimationClip>()
+                .register_inspectable::<AnimationIndex>()
+                .register_inspectable::<state_machine::StateMachine>()
+                .register_type::<AnimationTimer>();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub(crate) enum AnimationSystem {
+    Transition,
+    /// Switches to the picked clip, so the new clip's first frame is shown in the same frame.
+    Play,
+}
+
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct AnimationClip {
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    clips: Handle<clip::Clips>,
+    name: String,
+    speed: f32,
+}
+
+#[derive(Component, Default)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct AnimationIndex {
+    range: std::ops::Range<usize>,
+    playback: clip::Playback,
+    reverse: bool,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    durations: Vec<f32>,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    events: Vec<Vec<String>>,
+    speed: f32,
+    position: usize,
+    forward: bool,
+    finished: bool,
+}
+
+#[derive(Component, Deref, DerefMut)]
+#[cfg_attr(feature = "editor", derive(Default, Reflect), reflect(Component))]
+pub(crate) struct AnimationTimer(Timer);
+
+/// Sent when a frame tagged with `name` is entered.
+pub(crate) struct AnimationEvent {
+    pub(crate) entity: Entity,
+    pub(crate) name: String,
+}
+
+/// Sent when a [`clip::Playback::Once`] clip has played its last frame.
+pub(crate) struct AnimationFinished {
+    pub(crate) entity: Entity,
+}
+
+impl AnimationClip {
+    pub(crate) fn new(clips: Handle<clip::Clips>, name: impl Into<String>) -> AnimationClip {
+        AnimationClip {
+            clips,
+            name: name.into(),
+            speed: 1.0,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub(crate) fn play(&mut self, name: impl Into<String>, speed: f32) {
+        self.name = name.into();
+        self.speed = speed;
+    }
+}
+
+impl AnimationIndex {
+    /// Switches to a new clip, restarting playback only if its frames or playback changed.
+    fn change(&mut self, clip: &clip::Clip, speed: f32) -> bool {
+        let range = clip.start..(clip.start + clip.length);
+
+        self.durations = (0..clip.length).map(|i| clip.duration(i)).collect();
+        self.events = (0..clip.length).map(|i| clip.events(i).to_vec()).collect();
+        self.speed = speed;
+
+        if self.range == range && self.playback == clip.playback && self.reverse == clip.reverse {
+            return false;
+        }
+
+        self.range = range;
+        self.playback = clip.playback;
+        self.reverse = clip.reverse;
+        self.position = 0;
+        self.forward = true;
+        self.finished = false;
+        true
+    }
+
+    /// Steps to the next frame, returning `true` when a one-shot clip has just finished.
+    fn advance(&mut self) -> bool {
+        let last = self.range.len().saturating_sub(1);
+
+        if self.finished {
+            return false;
+        }
+
+        // a single frame has nowhere to go, but a one-shot clip of one still ends
+        if last == 0 {
+            self.finished = self.playback == clip::Playback::Once;
+            return self.finished;
+        }
+
+        match self.playback {
+            clip::Playback::Loop => {
+                self.position = if self.position < last {
+                    self.position + 1
+                } else {
+                    0
+                };
+            }
+            clip::Playback::PingPong => {
+                if self.position == last {
+                    self.forward = false;
+                } else if self.position == 0 {
+                    self.forward = true;
+                }
+
+                if self.forward {
+                    self.position += 1;
+                } else {
+                    self.position -= 1;
+                }
+            }
+            clip::Playback::Once => {
+                if self.position < last {
+                    self.position += 1;
+                } else {
+                    self.finished = true;
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn index(&self) -> usize {
+        self.range.start + self.offset()
+    }
+
+    /// Offset of the current frame from the start of the clip.
+    fn offset(&self) -> usize {
+        if self.reverse && !self.range.is_empty() {
+            self.range.len() - 1 - self.position
+        } else {
+            self.position
+        }
+    }
+
+    /// How long the current frame is shown for, taking playback speed into account.
+    fn duration(&self) -> Duration {
+        let duration = self.durations.get(self.offset()).copied().unwrap_or(0.0);
+        let speed = self.speed.max(MIN_SPEED);
+
+        Duration::from_secs_f32((duration / speed).max(MIN_FRAME_TIME))
+    }
+
+    fn events(&self) -> &[String] {
+        self.events.get(self.offset()).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl AnimationTimer {
+    pub(crate) fn new(timer: Timer) -> AnimationTimer {
+        AnimationTimer(timer)
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn animate(
+    time: Res<Time>,
+    mut animation_events: EventWriter<AnimationEvent>,
+    mut finished_events: EventWriter<AnimationFinished>,
+    mut query: Query<(
+        Entity,
+        &mut AnimationTimer,
+        &mut AnimationIndex,
+        &mut TextureAtlasSprite,
+    )>,
+) {
+    for (entity, mut timer, mut index, mut sprite) in query.iter_mut() {
+        timer.tick(time.delta());
+        if timer.just_finished() {
+            let position = index.position;
+
+            if index.advance() {
+                finished_events.send(AnimationFinished { entity });
+            }
+
+            if index.position != position {
+                sprite.index = index.index();
+                timer.set_duration(index.duration());
+
+                for name in index.events() {
+                    animation_events.send(AnimationEvent {
+                        entity,
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::type_complexity)]
+fn play(
+    clips: Res<Assets<clip::Clips>>,
+    mut asset_events: EventReader<AssetEvent<clip::Clips>>,
+    mut animation_events: EventWriter<AnimationEvent>,
+    mut query: Query<(
+        Entity,
+        ChangeTrackers<AnimationClip>,
+        &AnimationClip,
+        &mut AnimationIndex,
+        &mut AnimationTimer,
+        &mut TextureAtlasSprite,
+    )>,
+) {
+    let loaded = asset_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, tracker, animation_clip, mut animation_index, mut animation_timer, mut sprite) in
+        query.iter_mut()
+    {
+        if !tracker.is_changed() && !loaded.contains(&&animation_clip.clips) {
+            continue;
+        }
+
+        let clip = clips
+            .get(&animation_clip.clips)
+            .and_then(|clips| clips.get(&animation_clip.name));
+
+        if let Some(clip) = clip {
+            let restarted = animation_index.change(clip, animation_clip.speed);
+
+            animation_timer.set_duration(animation_index.duration());
+
+            if restarted {
+                animation_timer.reset();
+                sprite.index = animation_index.index();
+
+                for name in animation_index.events() {
+                    animation_events.send(AnimationEvent {
+                        entity,
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn index(length: usize, playback: clip::Playback, reverse: bool) -> AnimationIndex {
+        let mut index = AnimationIndex::default();
+        index.change(
+            &clip::Clip {
+                start: 10,
+                length,
+                frame_time: 0.1,
+                playback,
+                reverse,
+                frames: HashMap::default(),
+            },
+            1.0,
+        );
+        index
+    }
+
+    fn positions(index: &mut AnimationIndex, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                index.advance();
+                index.position
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_wraps_around() {
+        let mut index = index(3, clip::Playback::Loop, false);
+
+        assert_eq!(positions(&mut index, 5), [1, 2, 0, 1, 2]);
+        assert!(!index.finished);
+    }
+
+    #[test]
+    fn ping_pong_bounces() {
+        let mut index = index(3, clip::Playback::PingPong, false);
+
+        assert_eq!(positions(&mut index, 6), [1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let mut index = index(3, clip::Playback::Once, false);
+
+        assert!(!index.advance());
+        assert!(!index.advance());
+        assert!(index.advance());
+        assert_eq!(index.position, 2);
+
+        assert!(!index.advance());
+        assert_eq!(index.position, 2);
+    }
+
+    #[test]
+    fn single_frame_once_finishes() {
+        let mut index = index(1, clip::Playback::Once, false);
+
+        assert!(index.advance());
+        assert!(!index.advance());
+        assert_eq!(index.position, 0);
+    }
+
+    #[test]
+    fn single_frame_loop_never_finishes() {
+        let mut index = index(1, clip::Playback::Loop, false);
+
+        assert!(!index.advance());
+        assert!(!index.finished);
+    }
+
+    #[test]
+    fn reverse_counts_down_from_the_end() {
+        let mut index = index(3, clip::Playback::Once, true);
+
+        assert_eq!(index.index(), 12);
+        index.advance();
+        assert_eq!(index.index(), 11);
+        index.advance();
+        assert_eq!(index.index(), 10);
+    }
+
+    #[test]
+    fn changing_to_the_same_frames_keeps_playing() {
+        let mut index = index(3, clip::Playback::Loop, false);
+        index.advance();
+
+        let clip = clip::Clip {
+            start: 10,
+            length: 3,
+            frame_time: 0.2,
+            playback: clip::Playback::Loop,
+            reverse: false,
+            frames: HashMap::default(),
+        };
+
+        assert!(!index.change(&clip, 2.0));
+        assert_eq!(index.position, 1);
+    }
+}
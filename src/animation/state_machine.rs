@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+
+use crate::{
+    animation::{AnimationClip, AnimationFinished},
+    movement::{dodge, faces, locomotion},
+};
+
+/// Drives an [`AnimationClip`] from the movement components of the entity, or of its parent.
+///
+/// The first state added is the initial state, and the first transition that holds wins.
+#[derive(Component, Default)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct StateMachine {
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    states: Vec<State>,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    transitions: Vec<Transition>,
+    current: usize,
+    pending: Option<usize>,
+}
+
+pub(crate) struct State {
+    name: String,
+    clip: String,
+    speed: f32,
+}
+
+pub(crate) struct Transition {
+    from: Option<String>,
+    to: String,
+    condition: Condition,
+    clip: Option<String>,
+}
+
+pub(crate) enum Condition {
+    Faces(faces::Direction),
+    /// Whether the entity is trying to move.
+    Moving(bool),
+    /// Whether the entity is moving with the gait.
+    Gait(locomotion::Gait),
+    /// Whether the entity is in the middle of a dodge.
+    Dodging(bool),
+    All(Vec<Condition>),
+    #[allow(dead_code)]
+    Any(Vec<Condition>),
+    #[allow(dead_code)]
+    Not(Box<Condition>),
+}
+
+struct Context<'a> {
+    faces: Option<&'a faces::Faces>,
+    locomotion: Option<&'a locomotion::Locomotion>,
+    dodge: Option<&'a dodge::Dodge>,
+}
+
+impl StateMachine {
+    #[must_use]
+    pub(crate) fn with_state(mut self, state: State) -> StateMachine {
+        self.states.push(state);
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn with_transition(mut self, transition: Transition) -> StateMachine {
+        self.transitions.push(transition);
+        self
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.states.iter().position(|state| state.name == name)
+    }
+
+    fn next(&self, context: &Context) -> Option<(usize, Option<&str>)> {
+        self.transitions
+            .iter()
+            .filter(|transition| {
+                transition
+                    .from
+                    .as_ref()
+                    .is_none_or(|from| *from == self.states[self.current].name)
+            })
+            .filter(|transition| transition.to != self.states[self.current].name)
+            .find(|transition| transition.condition.evaluate(context))
+            .and_then(|transition| {
+                self.position(&transition.to)
+                    .map(|state| (state, transition.clip.as_deref()))
+            })
+    }
+}
+
+impl State {
+    pub(crate) fn new(name: impl Into<String>, clip: impl Into<String>) -> State {
+        State {
+            name: name.into(),
+            clip: clip.into(),
+            speed: 1.0,
+        }
+    }
+
+    /// Plays the clip `speed` times as fast. Has to be above zero.
+    #[must_use]
+    pub(crate) fn with_speed(mut self, speed: f32) -> State {
+        assert!(speed > 0.0, "state {:?} has speed {speed}", self.name);
+        self.speed = speed;
+        self
+    }
+}
+
+impl Transition {
+    /// A transition into `to` that may fire from any other state.
+    pub(crate) fn any(to: impl Into<String>, condition: Condition) -> Transition {
+        Transition {
+            from: None,
+            to: to.into(),
+            condition,
+            clip: None,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn new(
+        from: impl Into<String>,
+        to: impl Into<String>,
+        condition: Condition,
+    ) -> Transition {
+        Transition {
+            from: Some(from.into()),
+            to: to.into(),
+            condition,
+            clip: None,
+        }
+    }
+
+    /// Plays `clip`, which should be one-shot, before entering the target state.
+    #[allow(dead_code)]
+    #[must_use]
+    pub(crate) fn with_clip(mut self, clip: impl Into<String>) -> Transition {
+        self.clip = Some(clip.into());
+        self
+    }
+}
+
+impl Condition {
+    fn evaluate(&self, context: &Context) -> bool {
+        match self {
+            Condition::Faces(direction) => context
+                .faces
+                .is_some_and(|faces| faces.direction == *direction),
+            Condition::Moving(moving) => context
+                .locomotion
+                .is_some_and(|locomotion| locomotion.moving() == *moving),
+            Condition::Gait(gait) => context
+                .locomotion
+                .is_some_and(|locomotion| locomotion.moving() && locomotion.gait == *gait),
+            Condition::Dodging(dodging) => {
+                context.dodge.is_some_and(dodge::Dodge::dodging) == *dodging
+            }
+            Condition::All(conditions) => conditions.iter().all(|c| c.evaluate(context)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.evaluate(context)),
+            Condition::Not(condition) => !condition.evaluate(context),
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::type_complexity)]
+pub(crate) fn transition(
+    mut event_reader: EventReader<AnimationFinished>,
+    query_context: Query<(
+        Option<&faces::Faces>,
+        Option<&locomotion::Locomotion>,
+        Option<&dodge::Dodge>,
+    )>,
+    mut query: Query<(
+        Entity,
+        &mut StateMachine,
+        &mut AnimationClip,
+        Option<&Parent>,
+    )>,
+) {
+    let finished = event_reader
+        .iter()
+        .map(|event| event.entity)
+        .collect::<Vec<_>>();
+
+    for (entity, mut state_machine, mut animation_clip, parent) in query.iter_mut() {
+        if let Some(state) = state_machine.pending {
+            if finished.contains(&entity) {
+                state_machine.pending = None;
+                state_machine.current = state;
+            } else {
+                continue;
+            }
+        } else {
+            let owner = parent.map_or(entity, |parent| parent.0);
+            let Ok((faces, locomotion, dodge)) = query_context.get(owner) else {
+                continue;
+            };
+            let context = Context {
+                faces,
+                locomotion,
+                dodge,
+            };
+
+            let next = state_machine
+                .next(&context)
+                .map(|(state, clip)| (state, clip.map(ToString::to_string)));
+
+            match next {
+                Some((state, Some(clip))) => {
+                    animation_clip.play(clip, 1.0);
+                    state_machine.pending = Some(state);
+                    continue;
+                }
+                Some((state, None)) => state_machine.current = state,
+                None => {}
+            }
+        }
+
+        let Some(state) = state_machine.states.get(state_machine.current) else {
+            continue;
+        };
+
+        if animation_clip.name() != state.clip
+            || (animation_clip.speed() - state.speed).abs() > f32::EPSILON
+        {
+            animation_clip.play(state.clip.clone(), state.speed);
+        }
+    }
+}
@@ -1,19 +1,40 @@
-use bevy::prelude::*;
+use bevy::{
+    app::ScheduleRunnerPlugin, asset::AssetServerSettings, prelude::*,
+    render::settings::WgpuSettings, winit::WinitPlugin,
+};
 
 use crate::{
-    animation, camera,
-    movement::{faces, runs, walks},
-    object::{character, floor, mirror},
-    physics, player,
+    animation, atlas, camera, input, map,
+    movement::{dodge, faces, force, grid, locomotion, moves, surface},
+    object::{character, floor, hazard, mirror},
+    physics, player, replay, tilemap, timestep,
     visibility::z_index,
     window,
 };
 
 pub fn run() {
     let mut app = App::new();
+    let options = replay::Options::from_args(std::env::args().skip(1));
 
     app.insert_resource(window::descriptor())
-        .add_plugins(DefaultPlugins);
+        .insert_resource(AssetServerSettings {
+            watch_for_changes: true,
+            ..Default::default()
+        });
+
+    if options.headless {
+        app.insert_resource(WgpuSettings {
+            backends: None,
+            ..Default::default()
+        })
+        .add_plugins_with(DefaultPlugins, |group| group.disable::<WinitPlugin>())
+        .add_plugin(ScheduleRunnerPlugin);
+    } else {
+        app.add_plugins(DefaultPlugins);
+    }
+
+    app.insert_resource(input::config::load())
+        .insert_resource(options);
 
     #[cfg(feature = "editor")]
     {
@@ -21,16 +42,29 @@ pub fn run() {
         app.add_plugin(editor::Plugin);
     }
 
+    // the other plugins add systems to its stages
+    app.add_plugin(timestep::Plugin);
+
     app.add_plugin(animation::Plugin)
+        .add_plugin(atlas::Plugin)
         .add_plugin(camera::Plugin)
         .add_plugin(character::Plugin)
+        .add_plugin(dodge::Plugin)
         .add_plugin(faces::Plugin)
+        .add_plugin(force::Plugin)
         .add_plugin(floor::Plugin)
+        .add_plugin(grid::Plugin)
+        .add_plugin(hazard::Plugin)
+        .add_plugin(input::Plugin)
+        .add_plugin(locomotion::Plugin)
+        .add_plugin(map::Plugin)
         .add_plugin(mirror::Plugin)
+        .add_plugin(moves::Plugin)
         .add_plugin(physics::Plugin)
         .add_plugin(player::Plugin)
-        .add_plugin(walks::Plugin)
-        .add_plugin(runs::Plugin)
+        .add_plugin(replay::Plugin)
+        .add_plugin(surface::Plugin)
+        .add_plugin(tilemap::Plugin)
         .add_plugin(z_index::Plugin);
 
     app.run();
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Atlases>();
+    }
+}
+
+/// Texture atlases shared by every entity that slices the same image into the same grid.
+#[derive(Default)]
+pub(crate) struct Atlases(HashMap<(String, Grid), Handle<TextureAtlas>>);
+
+#[derive(Clone, Copy)]
+pub(crate) struct Grid {
+    pub(crate) tile_size: Vec2,
+    pub(crate) columns: usize,
+    pub(crate) rows: usize,
+    pub(crate) padding: Vec2,
+}
+
+impl Atlases {
+    pub(crate) fn grid(
+        &mut self,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlas>,
+        path: &str,
+        grid: Grid,
+    ) -> Handle<TextureAtlas> {
+        self.0
+            .entry((path.to_string(), grid))
+            .or_insert_with(|| {
+                texture_atlases.add(TextureAtlas::from_grid_with_padding(
+                    asset_server.load(path),
+                    grid.tile_size,
+                    grid.columns,
+                    grid.rows,
+                    grid.padding,
+                ))
+            })
+            .clone()
+    }
+}
+
+impl Grid {
+    fn key(&self) -> ([u32; 4], usize, usize) {
+        (
+            [
+                self.tile_size.x.to_bits(),
+                self.tile_size.y.to_bits(),
+                self.padding.x.to_bits(),
+                self.padding.y.to_bits(),
+            ],
+            self.columns,
+            self.rows,
+        )
+    }
+}
+
+impl PartialEq for Grid {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Grid {}
+
+impl std::hash::Hash for Grid {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
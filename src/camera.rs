@@ -1,15 +0,0 @@
-use bevy::prelude::*;
-
-pub(crate) struct Plugin;
-
-impl bevy::prelude::Plugin for Plugin {
-    fn build(&self, app: &mut App) {
-        app.add_startup_system(spawn);
-    }
-}
-
-pub(crate) fn spawn(mut commands: Commands) {
-    commands
-        .spawn_bundle(OrthographicCameraBundle::new_2d())
-        .insert(Name::new("camera"));
-}
@@ -0,0 +1,318 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use crate::camera::pixel_perfect::PixelPerfect;
+
+/// Zooms the camera to `scale`, where 2.0 shows twice as much of the world.
+pub(crate) struct ZoomEvent {
+    pub(crate) scale: f32,
+}
+
+/// Adds trauma to the camera, between 0.0 and 1.0. The shake grows with the square of the trauma.
+pub(crate) struct ShakeEvent {
+    pub(crate) trauma: f32,
+}
+
+/// Moves the camera to `target` over `duration` seconds and holds it there, or hands the camera
+/// back to the players when `target` is `None`.
+pub(crate) struct PanEvent {
+    pub(crate) target: Option<Target>,
+    pub(crate) duration: f32,
+    pub(crate) easing: Easing,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub(crate) enum Target {
+    Entity(Entity),
+    Position(Vec2),
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+pub(crate) enum Easing {
+    Linear,
+    In,
+    Out,
+    InOut,
+}
+
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Zoom {
+    pub(crate) target: f32,
+    pub(crate) min: f32,
+    pub(crate) max: f32,
+    /// How quickly the scale approaches the target, per second.
+    pub(crate) speed: f32,
+}
+
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Shake {
+    pub(crate) trauma: f32,
+    /// Trauma lost per second.
+    pub(crate) decay: f32,
+    pub(crate) max_offset: Vec2,
+    /// In radians.
+    pub(crate) max_angle: f32,
+    pub(crate) frequency: f32,
+}
+
+/// A scripted camera move. Following the players is suspended while it is present.
+#[derive(Component)]
+pub(crate) struct Pan {
+    from: Vec2,
+    target: Target,
+    elapsed: f32,
+    duration: f32,
+    easing: Easing,
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Zoom {
+            target: 1.0,
+            min: 0.25,
+            max: 4.0,
+            speed: 8.0,
+        }
+    }
+}
+
+impl Default for Shake {
+    fn default() -> Self {
+        Shake {
+            trauma: 0.0,
+            decay: 1.0,
+            max_offset: Vec2::new(16.0, 16.0),
+            max_angle: 0.05,
+            frequency: 15.0,
+        }
+    }
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::In => t * t * t,
+            Easing::Out => 1.0 - (1.0 - t).powi(3),
+            Easing::InOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+impl Zoom {
+    /// Scales that keep every pixel of the art the same size on screen: whole fractions when
+    /// zoomed in, whole numbers when zoomed out.
+    fn whole(scale: f32) -> f32 {
+        if scale < 1.0 {
+            1.0 / (1.0 / scale).round()
+        } else {
+            scale.round()
+        }
+    }
+
+    /// The whole scale `steps` away from `scale`, zooming in for positive steps.
+    #[allow(clippy::cast_precision_loss)]
+    fn step(scale: f32, steps: i32) -> f32 {
+        // zoomed in by n is level n - 1, zoomed out by n is level 1 - n
+        let level = if scale < 1.0 {
+            (1.0 / scale).round() - 1.0
+        } else {
+            1.0 - scale.round()
+        } + steps as f32;
+
+        if level >= 0.0 {
+            1.0 / (level + 1.0)
+        } else {
+            1.0 - level
+        }
+    }
+}
+
+/// Smooth noise between -1.0 and 1.0, made of a few incommensurate sine waves.
+fn noise(t: f32, seed: f32) -> f32 {
+    0.5 * (t + seed).sin() + 0.3 * (2.3 * t + 1.7 * seed).sin() + 0.2 * (4.1 * t + 2.9 * seed).sin()
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::needless_pass_by_value)]
+pub(crate) fn scroll(
+    pixel_perfect: Res<PixelPerfect>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut zoom_events: EventWriter<ZoomEvent>,
+    query: Query<&Zoom>,
+) {
+    let lines = mouse_wheel_events
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 16.0,
+        })
+        .sum::<f32>();
+
+    if lines == 0.0 {
+        return;
+    }
+
+    for zoom in query.iter() {
+        let scale = if pixel_perfect.enabled {
+            Zoom::step(zoom.target, lines.signum() as i32)
+        } else {
+            zoom.target * 0.9_f32.powf(lines)
+        };
+
+        zoom_events.send(ZoomEvent { scale });
+    }
+}
+
+/// Eases towards the target scale, or jumps to the nearest whole scale when pixel perfect.
+#[allow(clippy::needless_pass_by_value)]
+pub(crate) fn zoom(
+    time: Res<Time>,
+    pixel_perfect: Res<PixelPerfect>,
+    mut zoom_events: EventReader<ZoomEvent>,
+    mut query: Query<(&mut Zoom, &mut OrthographicProjection)>,
+) {
+    let requested = zoom_events.iter().last().map(|event| event.scale);
+
+    for (mut zoom, mut projection) in query.iter_mut() {
+        if let Some(scale) = requested {
+            zoom.target = scale.clamp(zoom.min, zoom.max);
+        }
+
+        // anything in between would draw some pixels of the art larger than others
+        if pixel_perfect.enabled {
+            let scale = Zoom::whole(zoom.target);
+            if (projection.scale - scale).abs() > f32::EPSILON {
+                projection.scale = scale;
+            }
+            continue;
+        }
+
+        if (projection.scale - zoom.target).abs() > f32::EPSILON {
+            let t = 1.0 - (-zoom.speed * time.delta_seconds()).exp();
+            projection.scale += (zoom.target - projection.scale) * t;
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub(crate) fn pan(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pan_events: EventReader<PanEvent>,
+    query_target: Query<&GlobalTransform>,
+    mut query: Query<(Entity, &mut Transform, Option<&mut Pan>), With<super::Follow>>,
+) {
+    let requested = pan_events.iter().last();
+
+    for (entity, mut transform, pan) in query.iter_mut() {
+        match requested {
+            Some(PanEvent {
+                target: Some(target),
+                duration,
+                easing,
+            }) => {
+                commands.entity(entity).insert(Pan {
+                    from: transform.translation.truncate(),
+                    target: *target,
+                    elapsed: 0.0,
+                    duration: *duration,
+                    easing: *easing,
+                });
+                continue;
+            }
+            Some(PanEvent { target: None, .. }) => {
+                commands.entity(entity).remove::<Pan>();
+                continue;
+            }
+            None => {}
+        }
+
+        let Some(mut pan) = pan else {
+            continue;
+        };
+
+        let to = match pan.target {
+            Target::Entity(target) => match query_target.get(target) {
+                Ok(target) => target.translation.truncate(),
+                Err(_) => continue,
+            },
+            Target::Position(position) => position,
+        };
+
+        pan.elapsed += time.delta_seconds();
+        let t = if pan.duration > 0.0 {
+            (pan.elapsed / pan.duration).min(1.0)
+        } else {
+            1.0
+        };
+
+        let position = pan.from.lerp(to, pan.easing.apply(t));
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Offsets the global transform only, so following is not disturbed. Top level global transforms
+/// are rebuilt every frame, so offsets do not pile up.
+#[allow(clippy::cast_possible_truncation, clippy::needless_pass_by_value)]
+pub(crate) fn shake(
+    time: Res<Time>,
+    mut shake_events: EventReader<ShakeEvent>,
+    mut query: Query<(&mut Shake, &Transform, &mut GlobalTransform)>,
+) {
+    let trauma = shake_events.iter().map(|event| event.trauma).sum::<f32>();
+    let elapsed = time.seconds_since_startup() as f32;
+
+    for (mut shake, local, mut transform) in query.iter_mut() {
+        *transform = GlobalTransform::from(*local);
+
+        shake.trauma = (shake.trauma + trauma - shake.decay * time.delta_seconds()).clamp(0.0, 1.0);
+        if shake.trauma <= 0.0 {
+            continue;
+        }
+
+        let t = elapsed * shake.frequency;
+        let strength = shake.trauma * shake.trauma;
+
+        transform.translation.x += shake.max_offset.x * strength * noise(t, 0.0);
+        transform.translation.y += shake.max_offset.y * strength * noise(t, 1.0);
+        transform.rotation *= Quat::from_rotation_z(shake.max_angle * strength * noise(t, 2.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_scales_step_through_fractions_and_multiples() {
+        let mut scale = 4.0;
+        let mut scales = vec![scale];
+        for _ in 0..6 {
+            scale = Zoom::step(scale, 1);
+            scales.push(scale);
+        }
+
+        assert_eq!(scales, [4.0, 3.0, 2.0, 1.0, 0.5, 1.0 / 3.0, 0.25]);
+        assert!((Zoom::step(0.25, -3) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn scales_round_to_the_nearest_whole_one() {
+        assert!((Zoom::whole(0.9) - 1.0).abs() < f32::EPSILON);
+        assert!((Zoom::whole(0.45) - 0.5).abs() < f32::EPSILON);
+        assert!((Zoom::whole(2.6) - 3.0).abs() < f32::EPSILON);
+    }
+}
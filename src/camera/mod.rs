@@ -0,0 +1,190 @@
+pub(crate) mod effects;
+pub(crate) mod pixel_perfect;
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{map, movement::faces, player};
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<effects::PanEvent>()
+            .add_event::<effects::ShakeEvent>()
+            .add_event::<effects::ZoomEvent>()
+            .add_startup_system(spawn)
+            .add_system(effects::pan)
+            .add_system(effects::scroll)
+            .add_system(effects::zoom)
+            .add_system(follow)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                effects::shake
+                    .label(CameraSystem::Shake)
+                    .after(TransformSystem::TransformPropagate),
+            );
+
+        pixel_perfect::build(app);
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Follow>()
+                .register_inspectable::<effects::Shake>()
+                .register_inspectable::<effects::Zoom>();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub(crate) enum CameraSystem {
+    Shake,
+}
+
+/// Moves the camera after the players, keeping it inside the room they are in.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Follow {
+    /// Half the size of the area around the camera's centre the players can move in freely.
+    pub(crate) dead_zone: Vec2,
+    /// How quickly the camera catches up, as the angular frequency of a critically damped spring.
+    pub(crate) smoothing: f32,
+    /// How far ahead of the players the camera looks in the direction they face.
+    pub(crate) look_ahead: f32,
+    velocity: Vec2,
+}
+
+impl Default for Follow {
+    fn default() -> Self {
+        Follow {
+            dead_zone: Vec2::new(48.0, 32.0),
+            smoothing: 6.0,
+            look_ahead: 48.0,
+            velocity: Vec2::ZERO,
+        }
+    }
+}
+
+pub(crate) fn spawn(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(Name::new("camera"))
+        .insert(Follow::default())
+        .insert(effects::Shake::default())
+        .insert(effects::Zoom::default());
+}
+
+/// Where in the world the cursor points, as seen by the camera at `transform`.
+#[cfg(feature = "editor")]
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+pub(crate) fn cursor_position(
+    window: &Window,
+    pixel_perfect: &pixel_perfect::PixelPerfect,
+    transform: &GlobalTransform,
+    projection: &OrthographicProjection,
+) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+
+    // the pixel perfect screen is centred in the window and scaled up by a whole number
+    let offset = if pixel_perfect.enabled {
+        let physical = Vec2::new(
+            window.physical_width() as f32,
+            window.physical_height() as f32,
+        );
+        let scale = pixel_perfect.scale(window) as f32;
+        (cursor * window.scale_factor() as f32 - physical / 2.0) / scale
+    } else {
+        cursor - Vec2::new(window.width(), window.height()) / 2.0
+    };
+
+    Some(transform.translation.truncate() + offset * projection.scale)
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::type_complexity)]
+fn follow(
+    time: Res<Time>,
+    query_player: Query<(&GlobalTransform, Option<&faces::Faces>), With<player::Player>>,
+    query_bounds: Query<&map::Bounds>,
+    mut query_camera: Query<
+        (&mut Follow, &mut Transform, &OrthographicProjection),
+        Without<effects::Pan>,
+    >,
+) {
+    let players = query_player
+        .iter()
+        .map(|(transform, faces)| {
+            let position = transform.translation.truncate();
+            (
+                position,
+                faces.map_or(Vec2::ZERO, |faces| faces.direction.vector()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if players.is_empty() {
+        return;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let count = players.len() as f32;
+    let position = players.iter().map(|(position, _)| position).sum::<Vec2>() / count;
+    let facing = players.iter().map(|(_, facing)| facing).sum::<Vec2>() / count;
+
+    let bounds = query_bounds
+        .iter()
+        .find(|bounds| bounds.contains(position))
+        .copied();
+
+    let delta = time.delta_seconds();
+
+    for (mut follow, mut transform, projection) in query_camera.iter_mut() {
+        let target = position + facing * follow.look_ahead;
+        let current = transform.translation.truncate();
+
+        // only move far enough to bring the target back to the edge of the dead zone
+        let mut desired = current
+            + (target - current).signum()
+                * ((target - current).abs() - follow.dead_zone).max(Vec2::ZERO);
+
+        if let Some(bounds) = bounds {
+            desired = clamp(desired, &bounds, projection);
+        }
+
+        // critically damped spring, integrated exactly so it is stable at any frame rate
+        let omega = follow.smoothing;
+        let offset = current - desired;
+        let decay = (-omega * delta).exp();
+        let temp = (follow.velocity + omega * offset) * delta;
+        follow.velocity = (follow.velocity - omega * temp) * decay;
+
+        let position = desired + (offset + temp) * decay;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+/// Keeps the view inside `bounds`, centring it on any axis the bounds are too small to fill.
+fn clamp(centre: Vec2, bounds: &map::Bounds, projection: &OrthographicProjection) -> Vec2 {
+    let half = Vec2::new(
+        projection.right - projection.left,
+        projection.top - projection.bottom,
+    ) * projection.scale
+        / 2.0;
+
+    let min = bounds.min + half;
+    let max = bounds.max - half;
+    let middle = (bounds.min + bounds.max) / 2.0;
+
+    Vec2::new(
+        if min.x <= max.x {
+            centre.x.clamp(min.x, max.x)
+        } else {
+            middle.x
+        },
+        if min.y <= max.y {
+            centre.y.clamp(min.y, max.y)
+        } else {
+            middle.y
+        },
+    )
+}
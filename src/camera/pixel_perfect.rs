@@ -0,0 +1,160 @@
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{Camera2d, RenderTarget},
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        RenderApp, RenderStage,
+    },
+    sprite::ExtractedSprites,
+    transform::TransformSystem,
+    window::WindowResized,
+};
+
+/// Renders the world at a fixed resolution and shows it scaled up by a whole number, so every
+/// pixel of the art covers the same number of screen pixels.
+pub(crate) struct PixelPerfect {
+    pub(crate) enabled: bool,
+    pub(crate) resolution: UVec2,
+}
+
+impl Default for PixelPerfect {
+    fn default() -> Self {
+        PixelPerfect {
+            enabled: true,
+            resolution: UVec2::new(640, 360),
+        }
+    }
+}
+
+impl PixelPerfect {
+    /// How many window pixels across each pixel of the resolution is shown as.
+    pub(crate) fn scale(&self, window: &Window) -> u32 {
+        let physical = UVec2::new(window.physical_width(), window.physical_height());
+        (physical / self.resolution).min_element().max(1)
+    }
+}
+
+/// The node showing the low resolution render, letterboxed in the window.
+#[derive(Component)]
+pub(crate) struct Screen;
+
+pub(crate) fn build(app: &mut App) {
+    let enabled = app
+        .world
+        .get_resource_or_insert_with(PixelPerfect::default)
+        .enabled;
+
+    if !enabled {
+        return;
+    }
+
+    app.add_startup_system_to_stage(StartupStage::PostStartup, setup)
+        .add_system(fit)
+        .add_system_to_stage(
+            CoreStage::PostUpdate,
+            snap_cameras
+                .after(TransformSystem::TransformPropagate)
+                .after(super::CameraSystem::Shake),
+        );
+
+    if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+        render_app.add_system_to_stage(RenderStage::Prepare, snap_sprites);
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn setup(
+    mut commands: Commands,
+    settings: Res<PixelPerfect>,
+    mut images: ResMut<Assets<Image>>,
+    mut query: Query<&mut Camera, With<Camera2d>>,
+) {
+    let size = Extent3d {
+        width: settings.resolution.x,
+        height: settings.resolution.y,
+        ..Default::default()
+    };
+
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Bgra8UnormSrgb,
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+
+    for mut camera in query.iter_mut() {
+        camera.target = RenderTarget::Image(image.clone());
+    }
+
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(Name::new("screen camera"));
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("letterbox"))
+        .with_children(|children| {
+            children
+                .spawn_bundle(ImageBundle {
+                    image: image.into(),
+                    ..Default::default()
+                })
+                .insert(Name::new("screen"))
+                .insert(Screen);
+        });
+}
+
+/// Sizes the screen to the largest whole multiple of the resolution that fits the window.
+#[allow(clippy::cast_possible_truncation, clippy::needless_pass_by_value)]
+fn fit(
+    settings: Res<PixelPerfect>,
+    windows: Res<Windows>,
+    mut resized_events: EventReader<WindowResized>,
+    query_added: Query<(), Added<Screen>>,
+    mut query: Query<&mut Style, With<Screen>>,
+) {
+    let resized = resized_events.iter().count() > 0;
+    if !resized && query_added.iter().next().is_none() {
+        return;
+    }
+
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+
+    let size =
+        (settings.resolution * settings.scale(window)).as_vec2() / window.scale_factor() as f32;
+
+    for mut style in query.iter_mut() {
+        style.size = Size::new(Val::Px(size.x), Val::Px(size.y));
+    }
+}
+
+/// Moves the world camera onto whole pixels, after its movement has been worked out.
+fn snap_cameras(mut query: Query<&mut GlobalTransform, With<Camera2d>>) {
+    for mut transform in query.iter_mut() {
+        transform.translation.x = transform.translation.x.round();
+        transform.translation.y = transform.translation.y.round();
+    }
+}
+
+/// Draws sprites on whole pixels without moving them, so physics keeps sub-pixel positions.
+fn snap_sprites(mut extracted_sprites: ResMut<ExtractedSprites>) {
+    for sprite in &mut extracted_sprites.sprites {
+        let translation = &mut sprite.transform.translation;
+        translation.x = translation.x.round();
+        translation.y = translation.y.round();
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+
+use crate::input::{Action, Bindings, Scheme};
+
+const FILE: &str = "bindings.ron";
+
+/// Where the bindings are saved, in the user's config directory.
+pub(crate) fn path() -> Option<PathBuf> {
+    Some(directory()?.join(env!("CARGO_PKG_NAME")).join(FILE))
+}
+
+#[cfg(target_os = "windows")]
+fn directory() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn directory() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn directory() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+}
+
+/// The saved bindings, with the defaults for anything the file lacks or when it can't be read.
+pub(crate) fn load() -> Bindings {
+    let mut bindings = Bindings::default();
+
+    let Some(path) = path() else {
+        return bindings;
+    };
+
+    let Ok(saved) = fs::read_to_string(&path) else {
+        return bindings;
+    };
+
+    match ron::from_str::<Bindings>(&saved) {
+        Ok(saved) => {
+            for scheme in Scheme::ALL {
+                let (Some(layout), Some(saved)) =
+                    (bindings.0.get_mut(&scheme), saved.0.get(&scheme))
+                else {
+                    continue;
+                };
+
+                for action in Action::ALL {
+                    if let Some(saved) = saved.0.get(&action) {
+                        layout.0.insert(action, saved.clone());
+                    }
+                }
+            }
+        }
+        Err(error) => warn!("ignoring bindings in {}: {}", path.display(), error),
+    }
+
+    bindings
+}
+
+pub(crate) fn save(bindings: &Bindings) {
+    let Some(path) = path() else {
+        warn!("no config directory to save bindings to");
+        return;
+    };
+
+    if let Err(error) = write(&path, bindings) {
+        warn!("failed to save bindings to {}: {}", path.display(), error);
+    }
+}
+
+fn write(path: &Path, bindings: &Bindings) -> anyhow::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(
+        path,
+        ron::ser::to_string_pretty(bindings, PrettyConfig::default())?,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::input::Action;
+
+/// Dead zones of the left stick, as fractions of its full range.
+pub(crate) struct Sticks {
+    /// Below this the stick counts as centred.
+    pub(crate) dead_zone: f32,
+    /// Beyond this the stick runs rather than walks.
+    pub(crate) run_zone: f32,
+}
+
+impl Default for Sticks {
+    fn default() -> Self {
+        Sticks {
+            dead_zone: 0.25,
+            run_zone: 0.9,
+        }
+    }
+}
+
+/// The left stick of `gamepad`, with the dead zone removed.
+pub(crate) fn stick(gamepad: Gamepad, axes: &Axis<GamepadAxis>, sticks: &Sticks) -> Vec2 {
+    let value = |axis_type| axes.get(GamepadAxis(gamepad, axis_type)).unwrap_or(0.0);
+    let stick = Vec2::new(
+        value(GamepadAxisType::LeftStickX),
+        value(GamepadAxisType::LeftStickY),
+    );
+
+    // rescaled so movement starts from nothing at the edge of the dead zone
+    let length = stick.length().min(1.0);
+    if length <= sticks.dead_zone {
+        return Vec2::ZERO;
+    }
+    stick.normalize() * (length - sticks.dead_zone) / (1.0 - sticks.dead_zone)
+}
+
+/// The actions a stick presses: the direction it mostly points in, and running when pushed far.
+pub(crate) fn actions(stick: Vec2, sticks: &Sticks) -> Vec<Action> {
+    if stick == Vec2::ZERO {
+        return Vec::new();
+    }
+
+    let direction = if stick.x.abs() > stick.y.abs() {
+        if stick.x > 0.0 {
+            Action::MoveRight
+        } else {
+            Action::MoveLeft
+        }
+    } else if stick.y > 0.0 {
+        Action::MoveUp
+    } else {
+        Action::MoveDown
+    };
+
+    let run_zone = (sticks.run_zone - sticks.dead_zone) / (1.0 - sticks.dead_zone);
+    if stick.length() >= run_zone {
+        vec![direction, Action::Run]
+    } else {
+        vec![direction]
+    }
+}
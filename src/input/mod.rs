@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+pub(crate) mod config;
+pub(crate) mod gamepad;
+pub(crate) mod rebind;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bindings>()
+            .init_resource::<gamepad::Sticks>()
+            .init_resource::<Input<Action>>()
+            .init_resource::<rebind::Menu>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                action_input.label(ActionSystem).after(InputSystem),
+            )
+            .add_system(rebind::navigate)
+            .add_system(rebind::list.after(rebind::navigate));
+    }
+}
+
+/// Runs once the actions for the frame are known.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub(crate) struct ActionSystem;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub(crate) enum Action {
+    MoveUp,
+    MoveLeft,
+    MoveDown,
+    MoveRight,
+    Run,
+    Sneak,
+    Dodge,
+    Interact,
+    Menu,
+    Join,
+}
+
+/// A key, or a button on a gamepad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) enum Control {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+/// A chord of buttons that are all held to press an action.
+#[derive(Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct Binding(pub(crate) Vec<Control>);
+
+/// A set of bindings for one device, so players sharing a keyboard each get their own keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub(crate) enum Scheme {
+    KeyboardA,
+    KeyboardB,
+    Gamepad,
+}
+
+/// Where a player's input comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub(crate) enum Source {
+    /// Every source that no other player has claimed, so one player can use any device.
+    Any,
+    KeyboardA,
+    KeyboardB,
+    Gamepad(Gamepad),
+}
+
+/// The bindings of every action. Any one of an action's bindings presses it.
+#[derive(Clone, Deserialize, Serialize)]
+pub(crate) struct Layout(pub(crate) HashMap<Action, Vec<Binding>>);
+
+/// The layout of every scheme.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct Bindings(pub(crate) HashMap<Scheme, Layout>);
+
+/// Everything needed to work out the actions pressed by a source.
+#[derive(SystemParam)]
+pub(crate) struct Controls<'w, 's> {
+    menu: Res<'w, rebind::Menu>,
+    bindings: Res<'w, Bindings>,
+    sticks: Res<'w, gamepad::Sticks>,
+    gamepads: Res<'w, Gamepads>,
+    keys: Res<'w, Input<KeyCode>>,
+    buttons: Res<'w, Input<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    #[system_param(ignore)]
+    marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl Action {
+    pub(crate) const ALL: [Action; 10] = [
+        Action::MoveUp,
+        Action::MoveLeft,
+        Action::MoveDown,
+        Action::MoveRight,
+        Action::Run,
+        Action::Sneak,
+        Action::Dodge,
+        Action::Interact,
+        Action::Menu,
+        Action::Join,
+    ];
+}
+
+impl Binding {
+    fn pressed(&self, held: impl Fn(Control) -> bool) -> bool {
+        !self.0.is_empty() && self.0.iter().all(|button| held(*button))
+    }
+
+    /// Whether every button of this binding is also part of `other`, which has more buttons.
+    fn within(&self, other: &Binding) -> bool {
+        other.0.len() > self.0.len() && self.0.iter().all(|button| other.0.contains(button))
+    }
+}
+
+impl Scheme {
+    pub(crate) const ALL: [Scheme; 3] = [Scheme::KeyboardA, Scheme::KeyboardB, Scheme::Gamepad];
+}
+
+impl Source {
+    /// Every source other than [`Source::Any`] that can be played with, keyboards first.
+    pub(crate) fn connected(gamepads: &Gamepads) -> Vec<Source> {
+        let mut gamepads = gamepads.iter().copied().collect::<Vec<_>>();
+        gamepads.sort_by_key(|gamepad| gamepad.0);
+
+        [Source::KeyboardA, Source::KeyboardB]
+            .into_iter()
+            .chain(gamepads.into_iter().map(Source::Gamepad))
+            .collect()
+    }
+}
+
+impl Layout {
+    /// The action `binding` is bound to, if any.
+    pub(crate) fn action(&self, binding: &Binding) -> Option<Action> {
+        self.0
+            .iter()
+            .find(|(_, bindings)| bindings.contains(binding))
+            .map(|(action, _)| *action)
+    }
+
+    /// The actions pressed by the buttons that are `held`. A held chord hides the bindings made of
+    /// some of its buttons, so `LControl + S` does not also press the action bound to `S`.
+    pub(crate) fn pressed(&self, held: impl Fn(Control) -> bool + Copy) -> Vec<Action> {
+        let held = self
+            .0
+            .iter()
+            .flat_map(|(action, bindings)| bindings.iter().map(move |binding| (*action, binding)))
+            .filter(|(_, binding)| binding.pressed(held))
+            .collect::<Vec<_>>();
+
+        let mut actions = held
+            .iter()
+            .filter(|(_, binding)| !held.iter().any(|(_, chord)| binding.within(chord)))
+            .map(|(action, _)| *action)
+            .collect::<Vec<_>>();
+
+        actions.sort_by_key(|action| Action::ALL.iter().position(|other| other == action));
+        actions.dedup();
+        actions
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Control::{Gamepad, Key};
+        use GamepadButtonType as Pad;
+
+        let layout = |bindings: Vec<(Action, Vec<Control>)>| {
+            Layout(
+                bindings
+                    .into_iter()
+                    .map(|(action, buttons)| {
+                        (
+                            action,
+                            buttons
+                                .into_iter()
+                                .map(|button| Binding(vec![button]))
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+            )
+        };
+
+        let keyboard_a = layout(vec![
+            (Action::MoveUp, vec![Key(KeyCode::W)]),
+            (Action::MoveLeft, vec![Key(KeyCode::A)]),
+            (Action::MoveDown, vec![Key(KeyCode::S)]),
+            (Action::MoveRight, vec![Key(KeyCode::D)]),
+            (Action::Run, vec![Key(KeyCode::LShift)]),
+            (Action::Sneak, vec![Key(KeyCode::LControl)]),
+            (Action::Dodge, vec![Key(KeyCode::Q)]),
+            (Action::Interact, vec![Key(KeyCode::E), Key(KeyCode::Space)]),
+            (Action::Menu, vec![Key(KeyCode::Escape)]),
+            (Action::Join, vec![Key(KeyCode::Tab)]),
+        ]);
+
+        let keyboard_b = layout(vec![
+            (Action::MoveUp, vec![Key(KeyCode::Up)]),
+            (Action::MoveLeft, vec![Key(KeyCode::Left)]),
+            (Action::MoveDown, vec![Key(KeyCode::Down)]),
+            (Action::MoveRight, vec![Key(KeyCode::Right)]),
+            (Action::Run, vec![Key(KeyCode::RShift)]),
+            (Action::Sneak, vec![Key(KeyCode::RAlt)]),
+            (Action::Dodge, vec![Key(KeyCode::Slash)]),
+            (Action::Interact, vec![Key(KeyCode::RControl)]),
+            (Action::Menu, vec![Key(KeyCode::Escape)]),
+            (Action::Join, vec![Key(KeyCode::Return)]),
+        ]);
+
+        let gamepad = layout(vec![
+            (Action::MoveUp, vec![Gamepad(Pad::DPadUp)]),
+            (Action::MoveLeft, vec![Gamepad(Pad::DPadLeft)]),
+            (Action::MoveDown, vec![Gamepad(Pad::DPadDown)]),
+            (Action::MoveRight, vec![Gamepad(Pad::DPadRight)]),
+            (
+                Action::Run,
+                vec![Gamepad(Pad::RightTrigger2), Gamepad(Pad::West)],
+            ),
+            (Action::Sneak, vec![Gamepad(Pad::LeftTrigger2)]),
+            (Action::Dodge, vec![Gamepad(Pad::East)]),
+            (Action::Interact, vec![Gamepad(Pad::South)]),
+            (Action::Menu, vec![Gamepad(Pad::Start)]),
+            (Action::Join, vec![Gamepad(Pad::Select)]),
+        ]);
+
+        Bindings(HashMap::from([
+            (Scheme::KeyboardA, keyboard_a),
+            (Scheme::KeyboardB, keyboard_b),
+            (Scheme::Gamepad, gamepad),
+        ]))
+    }
+}
+
+impl Bindings {
+    pub(crate) fn layout(&self, scheme: Scheme) -> Option<&Layout> {
+        self.0.get(&scheme)
+    }
+}
+
+impl Controls<'_, '_> {
+    /// The actions pressed by `source`, leaving out the sources in `claimed` for [`Source::Any`].
+    /// Only the menu action is pressed while the rebind menu is open.
+    pub(crate) fn pressed(&self, source: Source, claimed: &[Source]) -> Vec<Action> {
+        let mut pressed = self.held(source, claimed);
+
+        if self.menu.is_open() {
+            pressed.retain(|action| *action == Action::Menu);
+        }
+
+        pressed
+    }
+
+    /// The actions pressed by `source`, whether or not the rebind menu is open.
+    fn held(&self, source: Source, claimed: &[Source]) -> Vec<Action> {
+        match source {
+            Source::Any => {
+                let mut pressed = Vec::new();
+                for source in Source::connected(&self.gamepads) {
+                    if !claimed.contains(&source) {
+                        pressed.extend(self.pressed_by(source));
+                    }
+                }
+                pressed.sort_by_key(|action| Action::ALL.iter().position(|other| other == action));
+                pressed.dedup();
+                pressed
+            }
+            source => self.pressed_by(source),
+        }
+    }
+
+    /// Where `source` wants to move, with a length up to 1.0. Only a stick gives less than that.
+    pub(crate) fn movement(&self, source: Source, claimed: &[Source]) -> Vec2 {
+        if self.menu.is_open() {
+            return Vec2::ZERO;
+        }
+
+        let sources = match source {
+            Source::Any => Source::connected(&self.gamepads)
+                .into_iter()
+                .filter(|source| !claimed.contains(source))
+                .collect(),
+            source => vec![source],
+        };
+
+        let stick = sources
+            .into_iter()
+            .filter_map(|source| match source {
+                Source::Gamepad(gamepad) if self.gamepads.contains(&gamepad) => {
+                    Some(gamepad::stick(gamepad, &self.axes, &self.sticks))
+                }
+                _ => None,
+            })
+            .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .unwrap_or(Vec2::ZERO);
+
+        if stick != Vec2::ZERO {
+            return stick;
+        }
+
+        let pressed = self.pressed(source, claimed);
+        let axis = |negative, positive| {
+            f32::from(u8::from(pressed.contains(&positive)))
+                - f32::from(u8::from(pressed.contains(&negative)))
+        };
+
+        Vec2::new(
+            axis(Action::MoveLeft, Action::MoveRight),
+            axis(Action::MoveDown, Action::MoveUp),
+        )
+        .normalize_or_zero()
+    }
+
+    fn pressed_by(&self, source: Source) -> Vec<Action> {
+        let (scheme, gamepad) = match source {
+            Source::Any => return Vec::new(),
+            Source::KeyboardA => (Scheme::KeyboardA, None),
+            Source::KeyboardB => (Scheme::KeyboardB, None),
+            Source::Gamepad(gamepad) => {
+                // a gamepad that was unplugged keeps its player until it comes back
+                if !self.gamepads.contains(&gamepad) {
+                    return Vec::new();
+                }
+                (Scheme::Gamepad, Some(gamepad))
+            }
+        };
+
+        let held = |button| match button {
+            Control::Key(key) => self.keys.pressed(key),
+            Control::Gamepad(button_type) => gamepad
+                .is_some_and(|gamepad| self.buttons.pressed(GamepadButton(gamepad, button_type))),
+        };
+
+        let mut pressed = self
+            .bindings
+            .layout(scheme)
+            .map(|layout| layout.pressed(held))
+            .unwrap_or_default();
+
+        if let Some(gamepad) = gamepad {
+            let stick = gamepad::stick(gamepad, &self.axes, &self.sticks);
+            for action in gamepad::actions(stick, &self.sticks) {
+                if !pressed.contains(&action) {
+                    pressed.push(action);
+                }
+            }
+        }
+
+        pressed
+    }
+}
+
+/// Presses and releases actions so they mirror the held buttons.
+pub(crate) fn update(actions: &mut Input<Action>, pressed: &[Action]) {
+    actions.clear();
+
+    for action in Action::ALL {
+        match (pressed.contains(&action), actions.pressed(action)) {
+            (true, false) => actions.press(action),
+            (false, true) => actions.release(action),
+            _ => {}
+        }
+    }
+}
+
+/// Presses the actions of every source, for anything not tied to one player such as menus.
+#[allow(clippy::needless_pass_by_value)]
+fn action_input(controls: Controls, mut actions: ResMut<Input<Action>>) {
+    update(&mut actions, &controls.held(Source::Any, &[]));
+}
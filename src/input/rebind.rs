@@ -0,0 +1,316 @@
+use bevy::{prelude::*, ui::entity::CameraUi};
+
+use crate::input::{config, Action, Binding, Bindings, Control, Scheme};
+
+const FONT: &str = "fonts/FiraSans-Bold.ttf";
+const FONT_SIZE: f32 = 20.0;
+
+/// The screen for rebinding actions, opened with [`Action::Menu`] or `Escape`. It is navigated
+/// with actions, so it works from any scheme.
+#[derive(Default)]
+pub(crate) struct Menu {
+    open: bool,
+    scheme: usize,
+    selected: usize,
+    listening: bool,
+    /// Whether resetting has been asked for once and waits to be confirmed.
+    resetting: bool,
+    notice: String,
+}
+
+/// The root node of the menu, and its camera, spawned while it is open.
+#[derive(Component)]
+pub(crate) struct Screen;
+
+#[derive(Component)]
+pub(crate) struct Listing;
+
+impl Menu {
+    pub(crate) fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn scheme(&self) -> Scheme {
+        Scheme::ALL[self.scheme]
+    }
+
+    fn action(&self) -> Action {
+        Action::ALL[self.selected]
+    }
+}
+
+impl Control {
+    fn label(self) -> String {
+        match self {
+            Control::Key(key) => format!("{key:?}"),
+            Control::Gamepad(button_type) => format!("Pad {button_type:?}"),
+        }
+    }
+}
+
+impl Action {
+    fn label(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveLeft => "Move left",
+            Action::MoveDown => "Move down",
+            Action::MoveRight => "Move right",
+            Action::Run => "Run",
+            Action::Sneak => "Sneak",
+            Action::Dodge => "Dodge",
+            Action::Interact => "Interact",
+            Action::Menu => "Menu",
+            Action::Join => "Join",
+        }
+    }
+}
+
+impl Scheme {
+    fn label(self) -> &'static str {
+        match self {
+            Scheme::KeyboardA => "Keyboard A",
+            Scheme::KeyboardB => "Keyboard B",
+            Scheme::Gamepad => "Gamepad",
+        }
+    }
+}
+
+/// The first control pressed this frame that can be bound in `scheme`.
+fn just_pressed(
+    scheme: Scheme,
+    keys: &Input<KeyCode>,
+    buttons: &Input<GamepadButton>,
+) -> Option<Control> {
+    match scheme {
+        Scheme::KeyboardA | Scheme::KeyboardB => {
+            keys.get_just_pressed().next().map(|key| Control::Key(*key))
+        }
+        Scheme::Gamepad => buttons
+            .get_just_pressed()
+            .next()
+            .map(|button| Control::Gamepad(button.1)),
+    }
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::too_many_arguments)]
+pub(crate) fn navigate(
+    mut commands: Commands,
+    actions: Res<Input<Action>>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut menu: ResMut<Menu>,
+    mut bindings: ResMut<Bindings>,
+    query_camera: Query<(), With<CameraUi>>,
+    query: Query<Entity, With<Screen>>,
+) {
+    // Escape works whatever the bindings, so the menu can always be reached to fix them
+    let toggled = actions.just_pressed(Action::Menu) || keys.just_pressed(KeyCode::Escape);
+
+    if !menu.open {
+        if toggled {
+            menu.open = true;
+            menu.resetting = false;
+            menu.notice.clear();
+            spawn(&mut commands, query_camera.is_empty());
+        }
+        return;
+    }
+
+    if menu.listening {
+        if keys.just_pressed(KeyCode::Escape) {
+            menu.listening = false;
+            menu.notice.clear();
+            return;
+        }
+
+        let Some(control) = just_pressed(menu.scheme(), &keys, &buttons) else {
+            return;
+        };
+        menu.listening = false;
+
+        let action = menu.action();
+        let binding = Binding(vec![control]);
+        let Some(layout) = bindings.0.get_mut(&menu.scheme()) else {
+            return;
+        };
+        menu.notice = match layout.action(&binding) {
+            Some(other) if other == action => format!("{} is already bound", control.label()),
+            Some(other) => format!(
+                "{} is already bound to {}",
+                control.label(),
+                other.label().to_lowercase()
+            ),
+            None => {
+                layout.0.entry(action).or_default().push(binding);
+                config::save(&bindings);
+                format!(
+                    "bound {} to {}",
+                    control.label(),
+                    action.label().to_lowercase()
+                )
+            }
+        };
+        return;
+    }
+
+    let navigated = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Interact,
+        Action::Dodge,
+        Action::Join,
+    ]
+    .into_iter()
+    .any(|action| actions.just_pressed(action));
+    if !toggled && !navigated {
+        return;
+    }
+
+    // anything else pressed in between calls resetting off
+    let resetting = std::mem::take(&mut menu.resetting);
+
+    if toggled {
+        menu.open = false;
+        for entity in query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    } else if actions.just_pressed(Action::MoveLeft) {
+        menu.scheme = (menu.scheme + Scheme::ALL.len() - 1) % Scheme::ALL.len();
+    } else if actions.just_pressed(Action::MoveRight) {
+        menu.scheme = (menu.scheme + 1) % Scheme::ALL.len();
+    } else if actions.just_pressed(Action::MoveUp) {
+        menu.selected = (menu.selected + Action::ALL.len() - 1) % Action::ALL.len();
+    } else if actions.just_pressed(Action::MoveDown) {
+        menu.selected = (menu.selected + 1) % Action::ALL.len();
+    } else if actions.just_pressed(Action::Interact) {
+        menu.listening = true;
+        menu.notice = format!("press a key for {}", menu.action().label().to_lowercase());
+    } else if actions.just_pressed(Action::Dodge) {
+        let action = menu.action();
+        // without it there would be no way back into the menu from a gamepad
+        menu.notice = if action == Action::Menu {
+            "the menu cannot be unbound".to_string()
+        } else {
+            if let Some(layout) = bindings.0.get_mut(&menu.scheme()) {
+                layout.0.insert(action, Vec::new());
+            }
+            config::save(&bindings);
+            format!("unbound {}", action.label().to_lowercase())
+        };
+    } else if actions.just_pressed(Action::Join) {
+        if resetting {
+            *bindings = Bindings::default();
+            config::save(&bindings);
+            menu.notice = "reset to defaults".to_string();
+        } else {
+            menu.resetting = true;
+            menu.notice = "press join again to reset every scheme to its defaults".to_string();
+        }
+    }
+}
+
+fn spawn(commands: &mut Commands, camera: bool) {
+    // the pixel perfect screen brings its own
+    if camera {
+        commands
+            .spawn_bundle(UiCameraBundle::default())
+            .insert(Name::new("menu camera"))
+            .insert(Screen);
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.75).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("rebind menu"))
+        .insert(Screen)
+        .with_children(|children| {
+            children
+                .spawn_bundle(TextBundle {
+                    text: Text {
+                        sections: Vec::new(),
+                        alignment: TextAlignment::default(),
+                    },
+                    ..Default::default()
+                })
+                .insert(Name::new("bindings"))
+                .insert(Listing);
+        });
+}
+
+/// Lists every action and its bindings, highlighting the selected one.
+#[allow(clippy::needless_pass_by_value)]
+pub(crate) fn list(
+    asset_server: Res<AssetServer>,
+    menu: Res<Menu>,
+    bindings: Res<Bindings>,
+    mut query: Query<(&mut Text, ChangeTrackers<Listing>)>,
+) {
+    let font = asset_server.load(FONT);
+
+    for (mut text, listing) in query.iter_mut() {
+        if !listing.is_added() && !menu.is_changed() && !bindings.is_changed() {
+            continue;
+        }
+
+        let section = |value: String, color: Color| TextSection {
+            value,
+            style: TextStyle {
+                font: font.clone(),
+                font_size: FONT_SIZE,
+                color,
+            },
+        };
+
+        let mut sections = vec![section(
+            format!("Controls: < {} >\n\n", menu.scheme().label()),
+            Color::WHITE,
+        )];
+        let layout = bindings.layout(menu.scheme());
+
+        for (i, action) in Action::ALL.into_iter().enumerate() {
+            let controls = layout
+                .and_then(|layout| layout.0.get(&action))
+                .into_iter()
+                .flatten()
+                .map(|binding| {
+                    binding
+                        .0
+                        .iter()
+                        .map(|control| control.label())
+                        .collect::<Vec<_>>()
+                        .join(" + ")
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let color = if i == menu.selected {
+                Color::YELLOW
+            } else {
+                Color::GRAY
+            };
+            sections.push(section(
+                format!("{}: {}\n", action.label(), controls),
+                color,
+            ));
+        }
+
+        sections.push(section(
+            "\nInteract: bind  Dodge: unbind  Join: reset  Menu: close\n".to_string(),
+            Color::GRAY,
+        ));
+        sections.push(section(menu.notice.clone(), Color::WHITE));
+
+        text.sections = sections;
+    }
+}
@@ -2,13 +2,19 @@
 
 mod animation;
 pub mod app;
+mod atlas;
 mod camera;
 #[cfg(feature = "editor")]
 mod editor;
+mod input;
+mod map;
 mod movement;
 mod object;
 mod physics;
 mod player;
+mod replay;
+mod tilemap;
+mod timestep;
 mod visibility;
 mod window;
 
@@ -0,0 +1,443 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::bail;
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::map::{resolve, Layer, Map, Movement, Object, Shape, Terrain, Tile, TileTag};
+
+/// The `IntGrid` value identifier of solid cells. Any other is painted as a terrain.
+const COLLIDER: &str = "collider";
+
+/// The level field choosing between `"free"` and `"grid"` movement.
+const MOVEMENT: &str = "movement";
+
+/// Loads `LDtk` projects saved as `*.ldtk`. Levels after the first are labeled assets named after
+/// their identifier, e.g. `room/world.ldtk#Level_1`.
+#[derive(Default)]
+pub(crate) struct Loader;
+
+#[derive(Deserialize)]
+struct Project {
+    defs: Definitions,
+    levels: Vec<Level>,
+}
+
+#[derive(Deserialize)]
+struct Definitions {
+    layers: Vec<LayerDefinition>,
+    #[serde(default)]
+    tilesets: Vec<TilesetDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerDefinition {
+    uid: i64,
+    #[serde(default)]
+    int_grid_values: Vec<IntGridValue>,
+}
+
+/// Tiles are tagged with the values of an enum, such as `Ice`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TilesetDefinition {
+    rel_path: Option<String>,
+    #[serde(default)]
+    enum_tags: Vec<EnumTag>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EnumTag {
+    enum_value_id: String,
+    tile_ids: Vec<usize>,
+}
+
+#[derive(Deserialize)]
+struct IntGridValue {
+    value: i64,
+    identifier: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Level {
+    identifier: String,
+    #[serde(default)]
+    iid: Option<String>,
+    uid: i64,
+    world_x: f32,
+    world_y: f32,
+    px_wid: f32,
+    px_hei: f32,
+    external_rel_path: Option<String>,
+    layer_instances: Option<Vec<LayerInstance>>,
+    #[serde(default, rename = "__neighbours")]
+    neighbours: Vec<Neighbour>,
+    #[serde(default)]
+    field_instances: Vec<FieldInstance>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__cWid")]
+    columns: usize,
+    #[serde(rename = "__gridSize")]
+    grid_size: f32,
+    #[serde(rename = "__pxTotalOffsetX")]
+    offset_x: f32,
+    #[serde(rename = "__pxTotalOffsetY")]
+    offset_y: f32,
+    #[serde(rename = "__tilesetRelPath")]
+    tileset: Option<String>,
+    layer_def_uid: i64,
+    #[serde(default = "visible")]
+    visible: bool,
+    #[serde(default)]
+    int_grid_csv: Vec<i64>,
+    #[serde(default)]
+    grid_tiles: Vec<TileInstance>,
+    #[serde(default)]
+    auto_layer_tiles: Vec<TileInstance>,
+    #[serde(default)]
+    entity_instances: Vec<EntityInstance>,
+}
+
+#[derive(Deserialize)]
+struct TileInstance {
+    px: [f32; 2],
+    t: usize,
+}
+
+#[derive(Deserialize)]
+struct EntityInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__pivot")]
+    pivot: [f32; 2],
+    px: [f32; 2],
+    width: f32,
+    height: f32,
+}
+
+#[derive(Deserialize)]
+struct FieldInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: serde_json::Value,
+}
+
+/// Older projects refer to neighbours by `levelUid`, newer ones by `levelIid`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Neighbour {
+    level_iid: Option<String>,
+    level_uid: Option<i64>,
+    dir: String,
+}
+
+fn visible() -> bool {
+    true
+}
+
+impl AssetLoader for Loader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut project: Project = serde_json::from_slice(bytes)?;
+
+            let directory = load_context
+                .path()
+                .parent()
+                .map_or_else(PathBuf::new, Path::to_path_buf);
+
+            for level in &mut project.levels {
+                if let Some(external_rel_path) = &level.external_rel_path {
+                    let path = resolve(&directory, external_rel_path);
+                    let bytes = load_context.read_asset_bytes(&path).await?;
+                    *level = serde_json::from_slice(&bytes)?;
+                }
+            }
+
+            let path = load_context.path().to_string_lossy().replace('\\', "/");
+            let mut maps = maps(&project, &directory, &path)?.into_iter();
+
+            if let Some((_, map)) = maps.next() {
+                load_context.set_default_asset(LoadedAsset::new(map));
+            }
+            for (identifier, map) in maps {
+                load_context.set_labeled_asset(&identifier, LoadedAsset::new(map));
+            }
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+/// Builds every level of a project at `path`, whose external levels have been read in.
+fn maps(project: &Project, directory: &Path, path: &str) -> anyhow::Result<Vec<(String, Map)>> {
+    // the first level is the default asset, so it has no label to refer to
+    let paths = project
+        .levels
+        .iter()
+        .enumerate()
+        .flat_map(|(i, level)| {
+            let asset_path = if i == 0 {
+                path.to_string()
+            } else {
+                format!("{path}#{}", level.identifier)
+            };
+
+            [level.iid.clone(), Some(level.uid.to_string())]
+                .into_iter()
+                .flatten()
+                .map(move |key| (key, asset_path.clone()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let neighbour = |neighbour: &Neighbour| {
+        let key = neighbour
+            .level_iid
+            .clone()
+            .or_else(|| neighbour.level_uid.map(|uid| uid.to_string()))?;
+        paths.get(&key).cloned()
+    };
+
+    project
+        .levels
+        .iter()
+        .map(|level| {
+            Ok((
+                level.identifier.clone(),
+                map(&project.defs, level, directory, neighbour)?,
+            ))
+        })
+        .collect()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn map(
+    defs: &Definitions,
+    level: &Level,
+    directory: &Path,
+    neighbour: impl Fn(&Neighbour) -> Option<String> + Copy,
+) -> anyhow::Result<Map> {
+    let Some(layers) = &level.layer_instances else {
+        bail!("level {} has no layers", level.identifier);
+    };
+
+    let mut map = Map {
+        size: Vec2::new(level.px_wid, level.px_hei),
+        position: Some(Vec2::new(level.world_x, -(level.world_y + level.px_hei))),
+        // levels above or below this one are not adjacent
+        neighbours: level
+            .neighbours
+            .iter()
+            .filter(|neighbour| neighbour.dir != "<" && neighbour.dir != ">")
+            .filter_map(neighbour)
+            .collect(),
+        layers: Vec::new(),
+        terrains: Vec::new(),
+        tags: tags(defs, directory),
+        objects: Vec::new(),
+        movement: level
+            .field_instances
+            .iter()
+            .find(|field| field.identifier == MOVEMENT)
+            .and_then(|field| field.value.as_str())
+            .map(Movement::parse)
+            .transpose()?
+            .unwrap_or_default(),
+    };
+
+    // layers are listed from the top down
+    for layer in layers.iter().rev().filter(|layer| layer.visible) {
+        let offset = Vec2::new(layer.offset_x, layer.offset_y);
+
+        if let Some(tileset) = &layer.tileset {
+            let tileset = resolve(directory, tileset)
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            let tiles = layer
+                .grid_tiles
+                .iter()
+                .chain(&layer.auto_layer_tiles)
+                .map(|tile| Tile {
+                    tileset: tileset.clone(),
+                    index: tile.t,
+                    position: Vec2::from(tile.px) + offset + layer.grid_size / 2.0,
+                })
+                .collect::<Vec<_>>();
+            if !tiles.is_empty() {
+                map.layers.push(Layer {
+                    name: layer.identifier.clone(),
+                    tiles,
+                });
+            }
+        }
+
+        let identifiers = defs
+            .layers
+            .iter()
+            .find(|definition| definition.uid == layer.layer_def_uid)
+            .map(|definition| {
+                definition
+                    .int_grid_values
+                    .iter()
+                    .filter_map(|value| Some((value.value, value.identifier.as_deref()?)))
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default();
+
+        for (i, value) in layer.int_grid_csv.iter().enumerate() {
+            let Some(identifier) = identifiers.get(value) else {
+                continue;
+            };
+
+            let column = i % layer.columns;
+            let row = i / layer.columns;
+            let position = Vec2::new(column as f32, row as f32) * layer.grid_size + offset;
+
+            if *identifier == COLLIDER {
+                map.objects.push(Object {
+                    kind: COLLIDER.to_string(),
+                    position,
+                    shape: Shape::Rectangle(Vec2::splat(layer.grid_size)),
+                });
+            } else if layer.tileset.is_none() {
+                // layers with a tileset were already autotiled by LDtk's own rules
+                map.terrains.push(Terrain {
+                    name: (*identifier).to_string(),
+                    position: position + layer.grid_size / 2.0,
+                });
+            }
+        }
+
+        for entity in &layer.entity_instances {
+            let size = Vec2::new(entity.width, entity.height);
+
+            map.objects.push(Object {
+                kind: entity.identifier.to_lowercase(),
+                position: Vec2::from(entity.px) + offset - Vec2::from(entity.pivot) * size,
+                shape: Shape::Rectangle(size),
+            });
+        }
+    }
+
+    Ok(map)
+}
+
+fn tags(defs: &Definitions, directory: &Path) -> Vec<TileTag> {
+    let mut tags = Vec::new();
+
+    for definition in &defs.tilesets {
+        let Some(rel_path) = &definition.rel_path else {
+            continue;
+        };
+        let tileset = resolve(directory, rel_path)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        for tag in &definition.enum_tags {
+            tags.extend(tag.tile_ids.iter().map(|index| TileTag {
+                tileset: tileset.clone(),
+                index: *index,
+                tag: tag.enum_value_id.clone(),
+            }));
+        }
+    }
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world() -> Vec<(String, Map)> {
+        let project: Project =
+            serde_json::from_str(include_str!("../../assets/room/world.ldtk")).unwrap();
+
+        maps(&project, Path::new("room"), "room/world.ldtk").unwrap()
+    }
+
+    #[test]
+    fn builds_every_level_once() {
+        let identifiers = world()
+            .into_iter()
+            .map(|(identifier, _)| identifier)
+            .collect::<Vec<_>>();
+
+        assert_eq!(identifiers, ["Level_0", "Level_1"]);
+    }
+
+    #[test]
+    fn neighbours_refer_to_the_asset_each_level_is_loaded_as() {
+        let world = world();
+
+        assert_eq!(world[0].1.neighbours, ["room/world.ldtk#Level_1"]);
+        assert_eq!(world[1].1.neighbours, ["room/world.ldtk"]);
+    }
+
+    #[test]
+    fn reads_layers_and_fields() {
+        let world = world();
+        let map = &world[0].1;
+
+        assert_eq!(map.size, Vec2::new(192.0, 144.0));
+        assert_eq!(map.position, Some(Vec2::new(0.0, -144.0)));
+        assert_eq!(map.movement, Movement::Free);
+        assert_eq!(world[1].1.movement, Movement::Grid);
+
+        assert_eq!(map.layers.len(), 1);
+        assert_eq!(map.layers[0].name, "Tiles");
+        let tiles = &map.layers[0].tiles;
+        assert_eq!(tiles.len(), 1);
+        assert_eq!(tiles[0].tileset, "room/builder_48x48.png");
+        assert_eq!(tiles[0].position, Vec2::new(24.0, 72.0));
+
+        let colliders = map.objects.iter().filter(|object| object.kind == COLLIDER);
+        assert_eq!(colliders.count(), 4);
+        assert!(map
+            .terrains
+            .iter()
+            .all(|terrain| terrain.name == "wooden floor"));
+        assert_eq!(map.terrains.len(), 8);
+
+        let mirror = map.objects.iter().find(|object| object.kind == "mirror");
+        assert_eq!(mirror.unwrap().position, Vec2::new(72.0, 48.0));
+    }
+
+    #[test]
+    fn tags_come_from_enum_tags() {
+        let world = world();
+        let tags = world[0]
+            .1
+            .tags
+            .iter()
+            .map(|tag| (tag.index, tag.tag.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(tags, [(2542, "Ice"), (926, "Decoration")]);
+    }
+}
@@ -0,0 +1,484 @@
+pub(crate) mod ldtk;
+pub(crate) mod tiled;
+
+use std::{
+    collections::HashSet,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::bail;
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    object::{floor, hazard, mirror},
+    player,
+    tilemap::{self, autotile::Autotile},
+};
+
+const DIMENSION: f32 = 48.0;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Map>()
+            .add_event::<LoadEvent>()
+            .add_event::<UnloadEvent>()
+            .init_asset_loader::<ldtk::Loader>()
+            .init_asset_loader::<tiled::Loader>()
+            .add_startup_system(setup)
+            .add_system(load)
+            .add_system(reload)
+            .add_system(spawn)
+            .add_system(transition)
+            .add_system(unload);
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Bounds>()
+                .register_inspectable::<Movement>()
+                .register_inspectable::<Room>();
+        }
+    }
+}
+
+/// A room layout, in pixels from its top left corner with y pointing down.
+#[derive(TypeUuid)]
+#[uuid = "8d1f3c52-6a0b-4e7f-9c2d-3b5a7e1f0d64"]
+pub(crate) struct Map {
+    pub(crate) size: Vec2,
+    /// Where the bottom left corner of the map sits in the world, if the editor knows.
+    pub(crate) position: Option<Vec2>,
+    /// Asset paths of the maps adjacent to this one.
+    pub(crate) neighbours: Vec<String>,
+    /// Layers of tiles, from the bottom up.
+    pub(crate) layers: Vec<Layer>,
+    /// Cells painted with a terrain, whose tiles are picked by autotile rules.
+    pub(crate) terrains: Vec<Terrain>,
+    /// Tags the tilesets give their tiles, such as what they are made of.
+    pub(crate) tags: Vec<TileTag>,
+    pub(crate) objects: Vec<Object>,
+    pub(crate) movement: Movement,
+}
+
+pub(crate) struct Layer {
+    pub(crate) name: String,
+    pub(crate) tiles: Vec<Tile>,
+}
+
+pub(crate) struct Tile {
+    /// Asset path of the tileset image.
+    pub(crate) tileset: String,
+    pub(crate) index: usize,
+    /// Centre of the tile.
+    pub(crate) position: Vec2,
+}
+
+pub(crate) struct TileTag {
+    pub(crate) tileset: String,
+    pub(crate) index: usize,
+    pub(crate) tag: String,
+}
+
+pub(crate) struct Terrain {
+    pub(crate) name: String,
+    /// Centre of the cell.
+    pub(crate) position: Vec2,
+}
+
+pub(crate) struct Object {
+    pub(crate) kind: String,
+    pub(crate) position: Vec2,
+    pub(crate) shape: Shape,
+}
+
+pub(crate) enum Shape {
+    Point,
+    Rectangle(Vec2),
+    Ellipse(Vec2),
+    /// Vertices relative to the object's position.
+    Polygon(Vec<Vec2>),
+    /// Vertices relative to the object's position.
+    Polyline(Vec<Vec2>),
+}
+
+/// An instance of a [`Map`], placed with its bottom left corner at the entity's translation.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Room {
+    path: String,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    map: Handle<Map>,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    origin: Option<Vec2>,
+    /// Loaded with a [`LoadEvent`], so only an [`UnloadEvent`] despawns it.
+    pinned: bool,
+    spawned: bool,
+}
+
+/// The world space area covered by a spawned [`Room`].
+#[derive(Component, Clone, Copy)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Bounds {
+    pub(crate) min: Vec2,
+    pub(crate) max: Vec2,
+}
+
+/// How characters move around a spawned [`Room`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) enum Movement {
+    /// Anywhere, pushed around by physics.
+    #[default]
+    Free,
+    /// A whole tile at a time.
+    Grid,
+}
+
+/// Marks an entity that was spawned for, and is despawned with, a [`Room`].
+#[derive(Component)]
+pub(crate) struct Member(pub(crate) Entity);
+
+/// Requests a room for the map at `path`, placed where the map says without an `origin`.
+pub(crate) struct LoadEvent {
+    pub(crate) path: String,
+    pub(crate) origin: Option<Vec2>,
+}
+
+pub(crate) struct UnloadEvent {
+    pub(crate) room: Entity,
+}
+
+impl Map {
+    /// Converts a map position into a position relative to the room's origin.
+    fn local(&self, position: Vec2) -> Vec2 {
+        Vec2::new(position.x, self.size.y - position.y)
+    }
+}
+
+impl Movement {
+    /// Reads the `movement` property maps set to `"free"` or `"grid"`.
+    pub(crate) fn parse(value: &str) -> anyhow::Result<Movement> {
+        match value {
+            "free" => Ok(Movement::Free),
+            "grid" => Ok(Movement::Grid),
+            _ => bail!("unknown movement {value:?}"),
+        }
+    }
+}
+
+impl Bounds {
+    pub(crate) fn contains(&self, position: Vec2) -> bool {
+        position.cmpge(self.min).all() && position.cmplt(self.max).all()
+    }
+}
+
+impl Object {
+    fn centre(&self) -> Vec2 {
+        match &self.shape {
+            Shape::Rectangle(size) | Shape::Ellipse(size) => self.position + *size / 2.0,
+            Shape::Point | Shape::Polygon(_) | Shape::Polyline(_) => self.position,
+        }
+    }
+
+    fn collider(&self) -> Option<Collider> {
+        let vertices = |points: &[Vec2]| {
+            points
+                .iter()
+                .map(|point| Vec2::new(point.x, -point.y))
+                .collect::<Vec<_>>()
+        };
+
+        match &self.shape {
+            Shape::Point => None,
+            Shape::Rectangle(size) => Some(Collider::cuboid(size.x / 2.0, size.y / 2.0)),
+            // rapier has no ellipse, so the largest circle that fits is used instead
+            Shape::Ellipse(size) => Some(Collider::ball(size.min_element() / 2.0)),
+            Shape::Polygon(points) => Collider::convex_polyline(vertices(points)),
+            Shape::Polyline(points) => Some(Collider::polyline(vertices(points), None)),
+        }
+    }
+}
+
+/// Joins a path relative to a file in `directory`, resolving any `..` components.
+pub(crate) fn resolve(directory: &Path, relative: &str) -> PathBuf {
+    let mut path = PathBuf::new();
+
+    for component in directory.join(relative).components() {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::CurDir => {}
+            component => path.push(component),
+        }
+    }
+
+    path
+}
+
+fn spawn_room(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    path: &str,
+    origin: Option<Vec2>,
+    pinned: bool,
+) {
+    commands
+        .spawn()
+        .insert(Name::new(format!("room ({path})")))
+        .insert(Room {
+            path: path.to_string(),
+            map: asset_server.load(path),
+            origin,
+            pinned,
+            spawned: false,
+        })
+        .insert_bundle(TransformBundle::default());
+}
+
+fn despawn_room(
+    commands: &mut Commands,
+    room: Entity,
+    members: &Query<(Entity, &Member)>,
+    tilemaps: &mut Query<(&mut tilemap::Tilemap, Option<&mut Autotile>)>,
+) {
+    for (mut tilemap, autotile) in tilemaps.iter_mut() {
+        tilemap.remove_owned_by(room);
+        if let Some(mut autotile) = autotile {
+            autotile.remove_owned_by(room);
+        }
+    }
+
+    for (entity, _) in members.iter().filter(|(_, member)| member.0 == room) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.entity(room).despawn_recursive();
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn load(
+    mut event_reader: EventReader<LoadEvent>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    for event in event_reader.iter() {
+        spawn_room(
+            &mut commands,
+            &asset_server,
+            &event.path,
+            event.origin,
+            true,
+        );
+    }
+}
+
+/// Spawns rooms again when their map changes on disk.
+#[allow(clippy::needless_pass_by_value)]
+fn reload(
+    mut asset_events: EventReader<AssetEvent<Map>>,
+    mut load_events: EventWriter<LoadEvent>,
+    mut unload_events: EventWriter<UnloadEvent>,
+    query: Query<(Entity, &Room)>,
+) {
+    for event in asset_events.iter() {
+        let AssetEvent::Modified { handle } = event else {
+            continue;
+        };
+
+        for (entity, room) in query.iter().filter(|(_, room)| &room.map == handle) {
+            unload_events.send(UnloadEvent { room: entity });
+
+            // the others come back by themselves while a player is near
+            if room.pinned {
+                load_events.send(LoadEvent {
+                    path: room.path.clone(),
+                    origin: room.origin,
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn unload(
+    mut event_reader: EventReader<UnloadEvent>,
+    mut commands: Commands,
+    query_member: Query<(Entity, &Member)>,
+    mut query_tilemap: Query<(&mut tilemap::Tilemap, Option<&mut Autotile>)>,
+) {
+    for event in event_reader.iter() {
+        despawn_room(&mut commands, event.room, &query_member, &mut query_tilemap);
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn spawn(
+    maps: Res<Assets<Map>>,
+    mut commands: Commands,
+    mut layer_events: EventWriter<floor::LayerEvent>,
+    mut paint_events: EventWriter<floor::PaintEvent>,
+    mut hazard_events: EventWriter<hazard::CreateRequestEvent>,
+    mut mirror_events: EventWriter<mirror::CreateRequestEvent>,
+    mut query: Query<(Entity, &mut Room, &mut Transform)>,
+) {
+    for (entity, mut room, mut transform) in query.iter_mut() {
+        if room.spawned {
+            continue;
+        }
+
+        let Some(map) = maps.get(&room.map) else {
+            continue;
+        };
+
+        room.spawned = true;
+
+        let origin = room.origin.or(map.position).unwrap_or(Vec2::ZERO);
+        transform.translation = origin.extend(0.0);
+
+        commands
+            .entity(entity)
+            .insert(Bounds {
+                min: origin,
+                max: origin + map.size,
+            })
+            .insert(map.movement);
+
+        let mut tiles = map.layers.iter().flat_map(|layer| &layer.tiles);
+        if let Some(tile) = tiles.find(|tile| tile.tileset != floor::TEXTURE) {
+            warn!("unsupported tileset {}", tile.tileset);
+        }
+
+        let tags = map
+            .tags
+            .iter()
+            .filter(|tag| tag.tileset == floor::TEXTURE)
+            .map(|tag| (tag.index, tag.tag.clone()))
+            .collect::<Vec<_>>();
+
+        for (depth, layer) in map.layers.iter().enumerate() {
+            layer_events.send(floor::LayerEvent {
+                room: entity,
+                name: layer.name.clone(),
+                depth,
+                tiles: layer
+                    .tiles
+                    .iter()
+                    .filter(|tile| tile.tileset == floor::TEXTURE)
+                    .map(|tile| (map.local(tile.position), tile.index))
+                    .collect(),
+                tags: tags.clone(),
+            });
+        }
+
+        for terrain in &map.terrains {
+            let position = origin + map.local(terrain.position);
+
+            paint_events.send(floor::PaintEvent {
+                x: position.x,
+                y: position.y,
+                terrain: terrain.name.clone(),
+                room: Some(entity),
+            });
+        }
+
+        commands.entity(entity).with_children(|children| {
+            for object in &map.objects {
+                let position = map.local(object.centre());
+
+                match object.kind.as_str() {
+                    "mirror" => mirror_events.send(mirror::CreateRequestEvent {
+                        x: origin.x + position.x,
+                        y: origin.y + position.y,
+                        room: Some(entity),
+                    }),
+                    "hazard" => {
+                        if let Some(collider) = object.collider() {
+                            hazard_events.send(hazard::CreateRequestEvent {
+                                x: origin.x + position.x,
+                                y: origin.y + position.y,
+                                collider,
+                                room: Some(entity),
+                            });
+                        }
+                    }
+                    "collider" => {
+                        if let Some(collider) = object.collider() {
+                            children
+                                .spawn()
+                                .insert(Name::new("solid collider"))
+                                .insert(collider)
+                                .insert_bundle(TransformBundle::from(Transform::from_xyz(
+                                    position.x, position.y, 0.0,
+                                )));
+                        }
+                    }
+                    kind => warn!("unsupported object type {kind:?}"),
+                }
+            }
+        });
+    }
+}
+
+/// Keeps the rooms players are in, and their neighbours, loaded and despawns every other room
+/// that is not pinned.
+#[allow(clippy::needless_pass_by_value)]
+fn transition(
+    maps: Res<Assets<Map>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query_room: Query<(Entity, &Room, Option<&Bounds>)>,
+    query_member: Query<(Entity, &Member)>,
+    mut query_tilemap: Query<(&mut tilemap::Tilemap, Option<&mut Autotile>)>,
+    query_player: Query<&GlobalTransform, With<player::Player>>,
+) {
+    let occupied = query_room
+        .iter()
+        .filter_map(|(_, room, bounds)| {
+            let bounds = bounds?;
+            let map = maps.get(&room.map)?;
+
+            query_player
+                .iter()
+                .any(|transform| bounds.contains(transform.translation.truncate()))
+                .then_some((room, map))
+        })
+        .collect::<Vec<_>>();
+
+    // a player between rooms keeps everything loaded until they arrive somewhere
+    if occupied.is_empty() {
+        return;
+    }
+
+    let wanted = occupied
+        .iter()
+        .flat_map(|(room, map)| std::iter::once(&room.path).chain(&map.neighbours))
+        .collect::<HashSet<_>>();
+
+    let mut loaded = query_room
+        .iter()
+        .map(|(_, room, _)| &room.path)
+        .collect::<HashSet<_>>();
+    for path in &wanted {
+        if loaded.insert(path) {
+            spawn_room(&mut commands, &asset_server, path, None, false);
+        }
+    }
+
+    for (entity, room, _) in query_room.iter() {
+        if room.spawned && !room.pinned && !wanted.contains(&room.path) {
+            despawn_room(&mut commands, entity, &query_member, &mut query_tilemap);
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn setup(mut event_writer: EventWriter<LoadEvent>) {
+    event_writer.send(LoadEvent {
+        path: "room/01.tmx".to_string(),
+        origin: Some(Vec2::new(-6.5 * DIMENSION, -4.5 * DIMENSION)),
+    });
+}
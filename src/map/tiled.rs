@@ -0,0 +1,316 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+
+use crate::map::{resolve, Layer, Map, Movement, Object, Shape, Terrain, Tile, TileTag};
+
+const FLIPPED_FLAGS: u32 = 0xE000_0000;
+
+/// Loads orthogonal, finite maps saved by Tiled as `*.tmx`.
+#[derive(Default)]
+pub(crate) struct Loader;
+
+struct Tileset {
+    first_gid: u32,
+    image: String,
+    /// The class tiles are given, by their index in the tileset.
+    tags: Vec<(usize, String)>,
+}
+
+impl AssetLoader for Loader {
+    #[allow(clippy::cast_precision_loss)]
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let text = std::str::from_utf8(bytes)?;
+            let document = roxmltree::Document::parse(text)?;
+            let map = document.root_element();
+
+            if map.attribute("orientation") != Some("orthogonal") {
+                bail!("only orthogonal maps are supported");
+            }
+            if map.attribute("infinite") == Some("1") {
+                bail!("infinite maps are not supported");
+            }
+
+            let directory = load_context
+                .path()
+                .parent()
+                .map_or_else(PathBuf::new, Path::to_path_buf);
+
+            let mut tilesets = Vec::new();
+            for node in map.children().filter(|node| node.has_tag_name("tileset")) {
+                let first_gid = attribute(node, "firstgid")?;
+
+                let tileset = match node.attribute("source") {
+                    Some(source) => {
+                        let path = resolve(&directory, source);
+                        let bytes = load_context.read_asset_bytes(&path).await?;
+                        let text = std::str::from_utf8(&bytes)?;
+                        let document = roxmltree::Document::parse(text)?;
+                        let directory = path.parent().map_or_else(PathBuf::new, Path::to_path_buf);
+                        tileset(document.root_element(), &directory, first_gid)?
+                    }
+                    None => tileset(node, &directory, first_gid)?,
+                };
+
+                tilesets.push(tileset);
+            }
+            tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+            let columns: u32 = attribute(map, "width")?;
+            let rows: u32 = attribute(map, "height")?;
+            let tile_size = Vec2::new(attribute(map, "tilewidth")?, attribute(map, "tileheight")?);
+
+            let mut asset = Map {
+                size: Vec2::new(columns as f32, rows as f32) * tile_size,
+                position: None,
+                neighbours: Vec::new(),
+                layers: Vec::new(),
+                terrains: Vec::new(),
+                tags: tilesets
+                    .iter()
+                    .flat_map(|tileset| {
+                        tileset.tags.iter().map(|(index, tag)| TileTag {
+                            tileset: tileset.image.clone(),
+                            index: *index,
+                            tag: tag.clone(),
+                        })
+                    })
+                    .collect(),
+                objects: Vec::new(),
+                movement: property(map, "movement")
+                    .map(Movement::parse)
+                    .transpose()?
+                    .unwrap_or_default(),
+            };
+
+            layers(map, &tilesets, tile_size, &mut asset)?;
+
+            load_context.set_default_asset(LoadedAsset::new(asset));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn layers(
+    node: roxmltree::Node,
+    tilesets: &[Tileset],
+    tile_size: Vec2,
+    map: &mut Map,
+) -> anyhow::Result<()> {
+    for node in node.children().filter(roxmltree::Node::is_element) {
+        if node.attribute("visible") == Some("0") {
+            continue;
+        }
+
+        match node.tag_name().name() {
+            "group" => layers(node, tilesets, tile_size, map)?,
+            "layer" => {
+                let columns: u32 = attribute(node, "width")?;
+                let terrain = property(node, "terrain");
+                let mut tiles = Vec::new();
+
+                for (i, gid) in data(node)?.into_iter().enumerate() {
+                    let gid = gid & !FLIPPED_FLAGS;
+                    if gid == 0 {
+                        continue;
+                    }
+
+                    let i = u32::try_from(i)?;
+                    let position =
+                        (Vec2::new((i % columns) as f32, (i / columns) as f32) + 0.5) * tile_size;
+
+                    // the tile only stands in for the terrain while editing
+                    if let Some(terrain) = terrain {
+                        map.terrains.push(Terrain {
+                            name: terrain.to_string(),
+                            position,
+                        });
+                        continue;
+                    }
+
+                    let tileset = tilesets
+                        .iter()
+                        .rev()
+                        .find(|tileset| tileset.first_gid <= gid)
+                        .ok_or_else(|| anyhow!("no tileset for tile {gid}"))?;
+
+                    tiles.push(Tile {
+                        tileset: tileset.image.clone(),
+                        index: (gid - tileset.first_gid) as usize,
+                        position,
+                    });
+                }
+
+                if !tiles.is_empty() {
+                    map.layers.push(Layer {
+                        name: node.attribute("name").unwrap_or_default().to_string(),
+                        tiles,
+                    });
+                }
+            }
+            "objectgroup" => {
+                for node in node.children().filter(|node| node.has_tag_name("object")) {
+                    map.objects.push(object(node)?);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn object(node: roxmltree::Node) -> anyhow::Result<Object> {
+    // Tiled 1.9 renamed an object's `type` to `class`.
+    let kind = node
+        .attribute("class")
+        .or_else(|| node.attribute("type"))
+        .unwrap_or_default()
+        .to_string();
+    let position = Vec2::new(attribute(node, "x")?, attribute(node, "y")?);
+    let size = Vec2::new(
+        node.attribute("width").map_or(Ok(0.0), str::parse)?,
+        node.attribute("height").map_or(Ok(0.0), str::parse)?,
+    );
+
+    let shape = match node.children().find(roxmltree::Node::is_element) {
+        Some(child) if child.has_tag_name("point") => Shape::Point,
+        Some(child) if child.has_tag_name("ellipse") => Shape::Ellipse(size),
+        Some(child) if child.has_tag_name("polygon") => Shape::Polygon(points(child)?),
+        Some(child) if child.has_tag_name("polyline") => Shape::Polyline(points(child)?),
+        _ if node.attribute("gid").is_some() => {
+            // tile objects are anchored at their bottom left corner
+            return Ok(Object {
+                kind,
+                position: position - Vec2::new(0.0, size.y),
+                shape: Shape::Rectangle(size),
+            });
+        }
+        _ if size == Vec2::ZERO => Shape::Point,
+        _ => Shape::Rectangle(size),
+    };
+
+    Ok(Object {
+        kind,
+        position,
+        shape,
+    })
+}
+
+fn points(node: roxmltree::Node) -> anyhow::Result<Vec<Vec2>> {
+    node.attribute("points")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|point| {
+            let (x, y) = point
+                .split_once(',')
+                .ok_or_else(|| anyhow!("invalid point {point:?}"))?;
+            Ok(Vec2::new(x.parse()?, y.parse()?))
+        })
+        .collect()
+}
+
+fn tileset(node: roxmltree::Node, directory: &Path, first_gid: u32) -> anyhow::Result<Tileset> {
+    let image = node
+        .children()
+        .find(|node| node.has_tag_name("image"))
+        .and_then(|node| node.attribute("source"))
+        .ok_or_else(|| anyhow!("only single image tilesets are supported"))?;
+
+    // like objects, tiles had a `type` before Tiled 1.9 named it `class`
+    let tags = node
+        .children()
+        .filter(|node| node.has_tag_name("tile"))
+        .filter_map(|node| Some((node, node.attribute("class").or(node.attribute("type"))?)))
+        .map(|(node, tag)| Ok((attribute(node, "id")?, tag.to_string())))
+        .collect::<anyhow::Result<_>>()?;
+
+    Ok(Tileset {
+        first_gid,
+        image: resolve(directory, image)
+            .to_string_lossy()
+            .replace('\\', "/"),
+        tags,
+    })
+}
+
+fn data(layer: roxmltree::Node) -> anyhow::Result<Vec<u32>> {
+    let data = layer
+        .children()
+        .find(|node| node.has_tag_name("data"))
+        .ok_or_else(|| anyhow!("layer has no data"))?;
+    let text = data.text().unwrap_or_default().trim();
+
+    match (data.attribute("encoding"), data.attribute("compression")) {
+        (Some("csv"), None) => text
+            .split(',')
+            .map(|gid| gid.trim().parse().context("invalid tile"))
+            .collect(),
+        (Some("base64"), compression) => {
+            let bytes = base64::decode(text)?;
+            let bytes = match compression {
+                None => bytes,
+                Some("zlib") => decompress(flate2::read::ZlibDecoder::new(&bytes[..]))?,
+                Some("gzip") => decompress(flate2::read::GzDecoder::new(&bytes[..]))?,
+                Some(compression) => bail!("unsupported compression {compression:?}"),
+            };
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        (None, None) => data
+            .children()
+            .filter(|node| node.has_tag_name("tile"))
+            .map(|node| node.attribute("gid").map_or(Ok(0), str::parse))
+            .collect::<Result<_, _>>()
+            .context("invalid tile"),
+        (encoding, compression) => {
+            bail!("unsupported encoding {encoding:?} with compression {compression:?}")
+        }
+    }
+}
+
+fn decompress(mut reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn property<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|node| node.has_tag_name("properties"))?
+        .children()
+        .find(|node| node.has_tag_name("property") && node.attribute("name") == Some(name))?
+        .attribute("value")
+}
+
+fn attribute<T>(node: roxmltree::Node, name: &str) -> anyhow::Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    node.attribute(name)
+        .ok_or_else(|| anyhow!("<{}> is missing {name:?}", node.tag_name().name()))?
+        .parse()
+        .with_context(|| format!("<{}> has an invalid {name:?}", node.tag_name().name()))
+}
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    movement::{faces, force, grid, locomotion::LocomotionSystem},
+    physics,
+    timestep::{self, FixedSystems},
+};
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_fixed_system(
+            dodge
+                .label(LocomotionSystem::Dodge)
+                .after(LocomotionSystem::Stamina)
+                .before(LocomotionSystem::Move),
+        );
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Dodge>()
+                .register_inspectable::<Invulnerable>();
+        }
+    }
+}
+
+/// A short burst of speed the way the entity faces, taking over from its locomotion.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Dodge {
+    /// In pixels per second.
+    pub(crate) speed: f32,
+    /// How long the burst lasts, in seconds.
+    pub(crate) duration: f32,
+    /// How long after the start the entity is [`Invulnerable`], in seconds.
+    pub(crate) invulnerability: f32,
+    /// How long after the burst ends before the next dodge can start, in seconds.
+    pub(crate) cooldown: f32,
+    requested: bool,
+    velocity: Vec2,
+    /// Seconds since the current dodge started.
+    elapsed: Option<f32>,
+    /// Seconds left until the next dodge can start.
+    recharge: f32,
+}
+
+/// Present while the entity's colliders filter out [`physics::HOSTILE`] sensors, so hits pass it by.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Invulnerable;
+
+impl Default for Dodge {
+    fn default() -> Self {
+        Dodge {
+            speed: 384.0,
+            duration: 0.25,
+            invulnerability: 0.2,
+            cooldown: 0.5,
+            requested: false,
+            velocity: Vec2::ZERO,
+            elapsed: None,
+            recharge: 0.0,
+        }
+    }
+}
+
+impl Dodge {
+    /// Dodges on the next update, unless the entity is already dodging or cooling down.
+    pub(crate) fn start(&mut self) {
+        self.requested = true;
+    }
+
+    pub(crate) fn dodging(&self) -> bool {
+        self.elapsed.is_some()
+    }
+
+    pub(crate) fn velocity(&self) -> Option<Vec2> {
+        self.elapsed.map(|_| self.velocity)
+    }
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::type_complexity)]
+fn dodge(
+    mut commands: Commands,
+    query_collider: Query<Option<&CollisionGroups>, With<Collider>>,
+    mut query: Query<(
+        Entity,
+        &mut Dodge,
+        &faces::Faces,
+        Option<&grid::Steps>,
+        Option<&force::Forces>,
+        Option<&Invulnerable>,
+        Option<&Children>,
+    )>,
+) {
+    for (entity, mut dodge, faces, steps, forces, invulnerable, children) in query.iter_mut() {
+        // requests are dropped rather than held until the dodge is ready
+        let requested = std::mem::take(&mut dodge.requested);
+        // there is no bursting out of a tile in grid mode, or out of being knocked back
+        let held =
+            steps.is_some_and(grid::Steps::active) || forces.is_some_and(force::Forces::locked);
+        dodge.recharge = (dodge.recharge - timestep::DT).max(0.0);
+
+        if let Some(elapsed) = dodge.elapsed {
+            let elapsed = elapsed + timestep::DT;
+            if elapsed >= dodge.duration {
+                dodge.elapsed = None;
+                dodge.recharge = dodge.cooldown;
+            } else {
+                dodge.elapsed = Some(elapsed);
+            }
+        } else if requested && dodge.recharge <= 0.0 && !held {
+            dodge.elapsed = Some(0.0);
+            dodge.velocity = faces.direction.vector() * dodge.speed;
+        }
+
+        let vulnerable = dodge
+            .elapsed
+            .is_none_or(|elapsed| elapsed >= dodge.invulnerability);
+        match (vulnerable, invulnerable.is_some()) {
+            (false, false) => commands.entity(entity).insert(Invulnerable),
+            (true, true) => commands.entity(entity).remove::<Invulnerable>(),
+            _ => continue,
+        };
+
+        // colliders sit on the entity or its children
+        let colliders = std::iter::once(entity).chain(
+            children
+                .into_iter()
+                .flat_map(|children| children.iter())
+                .copied(),
+        );
+        for collider in colliders {
+            let Ok(groups) = query_collider.get(collider) else {
+                continue;
+            };
+
+            // only the hostile group is toggled, whatever else the collider is set up with
+            let mut groups = groups
+                .copied()
+                .unwrap_or_else(|| CollisionGroups::new(physics::ALL_GROUPS, physics::ALL_GROUPS));
+            if vulnerable {
+                groups.filters |= physics::HOSTILE;
+            } else {
+                groups.filters &= !physics::HOSTILE;
+            }
+            commands.entity(collider).insert(groups);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dodging_only_toggles_the_hostile_filter() {
+        let mut world = World::new();
+        let groups = CollisionGroups::new(1 << 3, (1 << 4) | physics::HOSTILE);
+        let entity = world
+            .spawn()
+            .insert(Dodge::default())
+            .insert(faces::Faces {
+                direction: faces::Direction::Down,
+            })
+            .insert(Collider::ball(1.0))
+            .insert(groups)
+            .id();
+        let mut stage = SystemStage::single_threaded().with_system(dodge);
+
+        world.get_mut::<Dodge>(entity).unwrap().start();
+        stage.run(&mut world);
+        assert!(world.get::<Invulnerable>(entity).is_some());
+        let dodging = world.get::<CollisionGroups>(entity).unwrap();
+        assert_eq!((dodging.memberships, dodging.filters), (1 << 3, 1 << 4));
+
+        while world.get::<Invulnerable>(entity).is_some() {
+            stage.run(&mut world);
+        }
+        let after = world.get::<CollisionGroups>(entity).unwrap();
+        assert_eq!(
+            (after.memberships, after.filters),
+            (groups.memberships, groups.filters)
+        );
+    }
+}
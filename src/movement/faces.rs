@@ -28,3 +28,38 @@ pub(crate) enum Direction {
     Right,
     Up,
 }
+
+impl Direction {
+    /// The direction closest to `vector`. Diagonals keep facing `self` when it is one of the two
+    /// closest, so the character doesn't flip back and forth between them.
+    pub(crate) fn towards(self, vector: Vec2) -> Direction {
+        let Some(vector) = vector.try_normalize() else {
+            return self;
+        };
+
+        if self.vector().dot(vector) >= std::f32::consts::FRAC_1_SQRT_2 - 1e-3 {
+            return self;
+        }
+
+        if vector.x.abs() > vector.y.abs() {
+            if vector.x > 0.0 {
+                Direction::Right
+            } else {
+                Direction::Left
+            }
+        } else if vector.y > 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        }
+    }
+
+    pub(crate) fn vector(self) -> Vec2 {
+        match self {
+            Direction::Down => -Vec2::Y,
+            Direction::Left => -Vec2::X,
+            Direction::Right => Vec2::X,
+            Direction::Up => Vec2::Y,
+        }
+    }
+}
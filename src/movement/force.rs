@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+
+use crate::{
+    movement::{grid, locomotion::LocomotionSystem, surface},
+    timestep::{self, FixedSystems},
+};
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ForceEvent>()
+            .add_event::<ImpulseEvent>()
+            .add_system(receive.label(ForceSystem))
+            .add_fixed_system(
+                force
+                    .label(LocomotionSystem::Force)
+                    .after(LocomotionSystem::Dodge)
+                    .before(LocomotionSystem::Move),
+            );
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Forces>();
+        }
+    }
+}
+
+/// Runs once the forces sent this frame are buffered for the steps to come.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub(crate) struct ForceSystem;
+
+/// Pushes an entity during the steps of the frame it is sent in, so it is sent every frame.
+pub(crate) struct ForceEvent {
+    pub(crate) entity: Entity,
+    /// In pixels per second, per second.
+    pub(crate) force: Vec2,
+}
+
+/// Knocks an entity back at once, such as from a hit.
+pub(crate) struct ImpulseEvent {
+    pub(crate) entity: Entity,
+    /// Added to the entity's velocity, in pixels per second.
+    pub(crate) impulse: Vec2,
+    /// How long the entity ignores what it is told to do, in seconds.
+    pub(crate) lock: Option<f32>,
+}
+
+/// Velocity from outside forces, added on top of the entity's locomotion.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Forces {
+    /// How quickly the velocity dies away, per second. Scaled by the traction of the surface.
+    pub(crate) drag: f32,
+    velocity: Vec2,
+    /// Seconds left until the entity moves as it is told again.
+    lock: f32,
+    /// Sent this frame, and applied by each of its steps.
+    force: Vec2,
+    /// Sent since the last step, and applied by the next one.
+    impulse: Vec2,
+    impulse_lock: f32,
+}
+
+impl Default for Forces {
+    fn default() -> Self {
+        Forces {
+            drag: 8.0,
+            velocity: Vec2::ZERO,
+            lock: 0.0,
+            force: Vec2::ZERO,
+            impulse: Vec2::ZERO,
+            impulse_lock: 0.0,
+        }
+    }
+}
+
+impl Forces {
+    pub(crate) fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    /// Whether the entity was knocked back too hard to move as it is told.
+    pub(crate) fn locked(&self) -> bool {
+        self.lock > 0.0
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn receive(
+    mut force_events: EventReader<ForceEvent>,
+    mut impulse_events: EventReader<ImpulseEvent>,
+    mut query: Query<&mut Forces>,
+) {
+    for mut forces in query.iter_mut() {
+        forces.force = Vec2::ZERO;
+    }
+
+    for event in force_events.iter() {
+        if let Ok(mut forces) = query.get_mut(event.entity) {
+            forces.force += event.force;
+        }
+    }
+
+    for event in impulse_events.iter() {
+        if let Ok(mut forces) = query.get_mut(event.entity) {
+            forces.impulse += event.impulse;
+            forces.impulse_lock = forces.impulse_lock.max(event.lock.unwrap_or_default());
+        }
+    }
+}
+
+fn force(mut query: Query<(&mut Forces, Option<&grid::Steps>, Option<&surface::Footing>)>) {
+    for (mut forces, steps, footing) in query.iter_mut() {
+        let impulse = std::mem::take(&mut forces.impulse);
+        let impulse_lock = std::mem::take(&mut forces.impulse_lock);
+
+        // stepping from tile to tile leaves nothing to push
+        if steps.is_some_and(grid::Steps::active) {
+            forces.velocity = Vec2::ZERO;
+            forces.lock = 0.0;
+            continue;
+        }
+
+        let traction = footing.map_or(1.0, |footing| footing.surface().traction());
+        let decay = (-forces.drag * traction * timestep::DT).exp();
+        forces.velocity *= decay;
+        forces.lock = (forces.lock - timestep::DT).max(0.0);
+
+        let force = forces.force;
+        forces.velocity += force * timestep::DT + impulse;
+        forces.lock = forces.lock.max(impulse_lock);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::{event::Events, system::Resource};
+
+    use super::*;
+
+    struct Game {
+        world: World,
+        frame: SystemStage,
+        step: SystemStage,
+        entity: Entity,
+    }
+
+    impl Game {
+        fn new() -> Game {
+            let mut world = World::new();
+            world.insert_resource(Events::<ForceEvent>::default());
+            world.insert_resource(Events::<ImpulseEvent>::default());
+            let entity = world.spawn().insert(Forces::default()).id();
+
+            Game {
+                world,
+                frame: SystemStage::single_threaded().with_system(receive),
+                step: SystemStage::single_threaded().with_system(force),
+                entity,
+            }
+        }
+
+        /// Runs a frame that takes `steps` steps.
+        fn run(&mut self, steps: usize) {
+            self.frame.run(&mut self.world);
+            for _ in 0..steps {
+                self.step.run(&mut self.world);
+            }
+        }
+
+        fn send<T: Resource>(&mut self, event: T) {
+            self.world
+                .get_resource_mut::<Events<T>>()
+                .unwrap()
+                .send(event);
+        }
+
+        fn forces(&self) -> &Forces {
+            self.world.get::<Forces>(self.entity).unwrap()
+        }
+    }
+
+    #[test]
+    fn impulses_knock_back_and_lock_for_a_while() {
+        let mut game = Game::new();
+        game.send(ImpulseEvent {
+            entity: game.entity,
+            impulse: Vec2::new(100.0, 0.0),
+            lock: Some(0.1),
+        });
+
+        game.run(1);
+        assert_eq!(game.forces().velocity(), Vec2::new(100.0, 0.0));
+        assert!(game.forces().locked());
+
+        game.run(10);
+        assert!(!game.forces().locked());
+        assert!(game.forces().velocity().x < 100.0);
+    }
+
+    #[test]
+    fn impulses_wait_for_a_step() {
+        let mut game = Game::new();
+        game.send(ImpulseEvent {
+            entity: game.entity,
+            impulse: Vec2::new(100.0, 0.0),
+            lock: None,
+        });
+
+        game.run(0);
+        game.run(1);
+        assert_eq!(game.forces().velocity(), Vec2::new(100.0, 0.0));
+
+        game.run(1);
+        assert!(game.forces().velocity().x < 100.0);
+    }
+
+    #[test]
+    fn forces_push_every_step_of_the_frame() {
+        let mut game = Game::new();
+        game.send(ForceEvent {
+            entity: game.entity,
+            force: Vec2::new(0.0, 60.0),
+        });
+
+        game.run(2);
+        let velocity = game.forces().velocity();
+        assert!(velocity.y > 1.0 && velocity.y < 2.0);
+
+        game.run(1);
+        assert!(game.forces().velocity().y < velocity.y);
+    }
+}
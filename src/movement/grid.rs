@@ -0,0 +1,235 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    map,
+    movement::{
+        faces,
+        locomotion::{Locomotion, LocomotionSystem},
+        moves, surface,
+    },
+    timestep::{self, FixedSystems},
+};
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_fixed_system(select.before(LocomotionSystem::Stamina))
+            .add_fixed_system(
+                step.after(LocomotionSystem::Stamina)
+                    .before(LocomotionSystem::Move),
+            );
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Steps>();
+        }
+    }
+}
+
+/// Moves an entity a tile at a time in [`map::Movement::Grid`] rooms. Its body is kinematic
+/// meanwhile, so physics doesn't nudge it off the grid.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Steps {
+    pub(crate) tile_size: f32,
+    /// The point, relative to the entity, that is kept on the centres of tiles.
+    pub(crate) anchor: Vec2,
+    /// Radius around the centre of the next tile that has to be clear to step into it.
+    pub(crate) clearance: f32,
+    enabled: bool,
+    /// The centre of a tile of the room the entity is on the grid of.
+    origin: Vec2,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    step: Option<Step>,
+}
+
+struct Step {
+    from: Vec2,
+    to: Vec2,
+    /// `None` when lining up with the grid rather than stepping.
+    direction: Option<faces::Direction>,
+    travelled: f32,
+}
+
+impl Steps {
+    pub(crate) fn new(tile_size: f32, anchor: Vec2) -> Steps {
+        Steps {
+            tile_size,
+            anchor,
+            clearance: tile_size * 0.4,
+            enabled: false,
+            origin: Vec2::ZERO,
+            step: None,
+        }
+    }
+
+    /// Whether the entity is on the grid, or finishing its last step before leaving it.
+    pub(crate) fn active(&self) -> bool {
+        self.enabled || self.step.is_some()
+    }
+
+    pub(crate) fn stepping(&self) -> bool {
+        self.step.is_some()
+    }
+
+    /// The centre of the tile of the current room nearest to `position`.
+    fn cell(&self, position: Vec2) -> Vec2 {
+        self.origin + ((position - self.origin) / self.tile_size).round() * self.tile_size
+    }
+}
+
+/// Puts entities on the grid of the room they enter, lined up with its nearest tile.
+#[allow(clippy::needless_pass_by_value)]
+fn select(
+    query_room: Query<(&map::Bounds, &map::Movement)>,
+    mut query: Query<(&Transform, &mut Steps)>,
+) {
+    for (transform, mut steps) in query.iter_mut() {
+        let position = transform.translation.truncate() + steps.anchor;
+        let origin = query_room
+            .iter()
+            .find(|(bounds, _)| bounds.contains(position))
+            .filter(|(_, movement)| **movement == map::Movement::Grid)
+            .map(|(bounds, _)| bounds.min + steps.tile_size / 2.0);
+
+        // rooms next to each other need not share a grid
+        let realign = origin.is_some_and(|origin| origin != steps.origin);
+        if origin.is_some() == steps.enabled && !realign {
+            continue;
+        }
+
+        // a step under way finishes on the grid it started on
+        if realign && steps.step.is_some() {
+            continue;
+        }
+
+        steps.enabled = origin.is_some();
+        if let Some(origin) = origin {
+            steps.origin = origin;
+        }
+
+        if steps.enabled && steps.step.is_none() {
+            steps.step = Some(Step {
+                from: position,
+                to: steps.cell(position),
+                direction: None,
+                travelled: 0.0,
+            });
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::type_complexity)]
+fn step(
+    rapier_context: Res<RapierContext>,
+    mut query: Query<(
+        Entity,
+        &mut Steps,
+        &Locomotion,
+        &moves::Moves,
+        &mut faces::Faces,
+        &mut Transform,
+        &mut RigidBody,
+        &mut Velocity,
+        Option<&surface::Footing>,
+    )>,
+) {
+    for (
+        entity,
+        mut steps,
+        locomotion,
+        moves,
+        mut faces,
+        mut transform,
+        mut body,
+        mut velocity,
+        footing,
+    ) in query.iter_mut()
+    {
+        let kinematic = steps.active();
+        if kinematic != (*body == RigidBody::KinematicPositionBased) {
+            *body = if kinematic {
+                RigidBody::KinematicPositionBased
+            } else {
+                RigidBody::Dynamic
+            };
+            *velocity = Velocity::zero();
+        }
+
+        if !kinematic {
+            continue;
+        }
+
+        let mut position = transform.translation.truncate() + steps.anchor;
+        // steps keep an even pace, so only the surface's speed matters here
+        let stride = footing.map_or(locomotion.stride(), |footing| {
+            footing.surface().apply(locomotion.stride())
+        });
+        let mut distance = stride.max_speed * timestep::DT;
+
+        if let Some(step) = &mut steps.step {
+            let length = step.from.distance(step.to);
+            step.travelled += distance;
+
+            if step.travelled >= length {
+                distance = step.travelled - length;
+                position = step.to;
+                steps.step = None;
+            } else {
+                position = step.from.lerp(step.to, step.travelled / length);
+                distance = 0.0;
+            }
+        }
+
+        // keep going without a pause while the input is held
+        if steps.enabled && steps.step.is_none() && moves.vector != Vec2::ZERO {
+            let direction = faces.direction;
+            let to = position + direction.vector() * steps.tile_size;
+
+            let occupied = rapier_context.intersection_with_shape(
+                to,
+                0.0,
+                &Collider::ball(steps.clearance),
+                QueryFilter::new()
+                    .exclude_sensors()
+                    .exclude_rigid_body(entity),
+            );
+
+            if occupied.is_none() {
+                steps.step = Some(Step {
+                    from: position,
+                    to,
+                    direction: Some(direction),
+                    travelled: distance,
+                });
+                position = position.lerp(to, distance / steps.tile_size);
+            }
+        }
+
+        // face the way the step goes, even if the input has turned since
+        if let Some(direction) = steps.step.as_ref().and_then(|step| step.direction) {
+            faces.direction = direction;
+        }
+
+        let translation = position - steps.anchor;
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_centred_on_the_tiles_of_the_room() {
+        let mut steps = Steps::new(48.0, Vec2::ZERO);
+        steps.origin = Vec2::new(-100.0, 20.0) + 24.0;
+
+        assert_eq!(steps.cell(Vec2::new(-70.0, 50.0)), Vec2::new(-76.0, 44.0));
+        assert_eq!(steps.cell(Vec2::new(0.0, 0.0)), Vec2::new(20.0, -4.0));
+    }
+}
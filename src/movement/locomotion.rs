@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    movement::{dodge, force, grid, moves, surface},
+    timestep::{self, FixedSystems},
+};
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExhaustedEvent>()
+            .add_event::<RecoveredEvent>()
+            .add_fixed_system(stamina.label(LocomotionSystem::Stamina))
+            .add_fixed_system(
+                locomote
+                    .label(LocomotionSystem::Move)
+                    .after(LocomotionSystem::Stamina),
+            );
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Locomotion>()
+                .register_inspectable::<Stamina>();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub(crate) enum LocomotionSystem {
+    /// Holds exhausted entities back from running.
+    Stamina,
+    /// Starts and times [`dodge::Dodge`]s.
+    Dodge,
+    /// Applies outside [`force::Forces`].
+    Force,
+    /// Moves entities along their [`moves::Moves`].
+    Move,
+}
+
+/// An entity ran out of stamina and can only walk until it recovers.
+pub(crate) struct ExhaustedEvent {
+    pub(crate) entity: Entity,
+}
+
+/// An exhausted entity got enough stamina back to run again.
+pub(crate) struct RecoveredEvent {
+    pub(crate) entity: Entity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) enum Gait {
+    Sneak,
+    Walk,
+    Run,
+}
+
+/// How a gait moves, in pixels per second.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Stride {
+    pub(crate) max_speed: f32,
+    /// How quickly speed builds up towards the max, per second.
+    pub(crate) acceleration: f32,
+    /// How quickly speed drops when slowing down or stopping, per second.
+    pub(crate) deceleration: f32,
+}
+
+/// Moves an entity along its [`moves::Moves`] at the speed of its gait, easing in and out of it.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Locomotion {
+    pub(crate) gait: Gait,
+    pub(crate) sneak: Stride,
+    pub(crate) walk: Stride,
+    pub(crate) run: Stride,
+    velocity: Vec2,
+    moving: bool,
+}
+
+/// Drains while running and regenerates otherwise. Running stops when it runs out and only
+/// starts again once it has recovered to `recovery` of `max`.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Stamina {
+    pub(crate) current: f32,
+    pub(crate) max: f32,
+    /// Lost per second while running.
+    pub(crate) drain: f32,
+    /// Gained per second while not running.
+    pub(crate) regeneration: f32,
+    pub(crate) recovery: f32,
+    exhausted: bool,
+}
+
+impl Default for Locomotion {
+    fn default() -> Self {
+        Locomotion {
+            gait: Gait::Walk,
+            sneak: Stride {
+                max_speed: 48.0,
+                acceleration: 480.0,
+                deceleration: 960.0,
+            },
+            walk: Stride {
+                max_speed: 96.0,
+                acceleration: 960.0,
+                deceleration: 1440.0,
+            },
+            run: Stride {
+                max_speed: 192.0,
+                acceleration: 960.0,
+                deceleration: 1440.0,
+            },
+            velocity: Vec2::ZERO,
+            moving: false,
+        }
+    }
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina {
+            current: 100.0,
+            max: 100.0,
+            drain: 25.0,
+            regeneration: 15.0,
+            recovery: 0.3,
+            exhausted: false,
+        }
+    }
+}
+
+impl Locomotion {
+    pub(crate) fn stride(&self) -> Stride {
+        match self.gait {
+            Gait::Sneak => self.sneak,
+            Gait::Walk => self.walk,
+            Gait::Run => self.run,
+        }
+    }
+
+    /// Whether the entity is trying to move, as opposed to still sliding to a stop.
+    pub(crate) fn moving(&self) -> bool {
+        self.moving
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn stamina(
+    mut exhausted_events: EventWriter<ExhaustedEvent>,
+    mut recovered_events: EventWriter<RecoveredEvent>,
+    mut query: Query<(Entity, &mut Stamina, &mut Locomotion)>,
+) {
+    for (entity, mut stamina, mut locomotion) in query.iter_mut() {
+        if stamina.exhausted && locomotion.gait == Gait::Run {
+            locomotion.gait = Gait::Walk;
+        }
+
+        if locomotion.moving && locomotion.gait == Gait::Run {
+            stamina.current = (stamina.current - stamina.drain * timestep::DT).max(0.0);
+            if stamina.current <= 0.0 {
+                stamina.exhausted = true;
+                exhausted_events.send(ExhaustedEvent { entity });
+            }
+        } else {
+            stamina.current =
+                (stamina.current + stamina.regeneration * timestep::DT).min(stamina.max);
+            if stamina.exhausted && stamina.current >= stamina.max * stamina.recovery {
+                stamina.exhausted = false;
+                recovered_events.send(RecoveredEvent { entity });
+            }
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value, clippy::type_complexity)]
+fn locomote(
+    mut query: Query<(
+        &mut Locomotion,
+        &moves::Moves,
+        &mut Velocity,
+        Option<&grid::Steps>,
+        Option<&surface::Footing>,
+        Option<&dodge::Dodge>,
+        Option<&force::Forces>,
+    )>,
+) {
+    for (mut locomotion, moves, mut velocity, steps, footing, dodge, forces) in query.iter_mut() {
+        let pushed = forces.map_or(Vec2::ZERO, force::Forces::velocity);
+
+        // stepping from tile to tile instead
+        if let Some(steps) = steps.filter(|steps| steps.active()) {
+            locomotion.velocity = Vec2::ZERO;
+            locomotion.moving = steps.stepping();
+            continue;
+        }
+
+        // the burst starts and stops dead, then eases back into the gait
+        if let Some(burst) = dodge.and_then(dodge::Dodge::velocity) {
+            locomotion.velocity = burst;
+            locomotion.moving = true;
+            velocity.linvel = burst + pushed;
+            continue;
+        }
+
+        let stride = footing.map_or(locomotion.stride(), |footing| {
+            footing.surface().apply(locomotion.stride())
+        });
+        // knocked back too hard to fight it
+        let vector = if forces.is_some_and(force::Forces::locked) {
+            Vec2::ZERO
+        } else {
+            moves.vector
+        };
+        let target = vector * stride.max_speed;
+
+        let rate = if target.length_squared() > locomotion.velocity.length_squared() {
+            stride.acceleration
+        } else {
+            stride.deceleration
+        };
+
+        let difference = target - locomotion.velocity;
+        let step = rate * timestep::DT;
+        locomotion.velocity = if difference.length() <= step {
+            target
+        } else {
+            locomotion.velocity + difference.normalize() * step
+        };
+        locomotion.moving = vector != Vec2::ZERO;
+
+        velocity.linvel = locomotion.velocity + pushed;
+    }
+}
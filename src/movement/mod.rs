@@ -1,3 +1,7 @@
+pub(crate) mod dodge;
 pub(crate) mod faces;
-pub(crate) mod runs;
-pub(crate) mod walks;
+pub(crate) mod force;
+pub(crate) mod grid;
+pub(crate) mod locomotion;
+pub(crate) mod moves;
+pub(crate) mod surface;
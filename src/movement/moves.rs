@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    #[allow(unused_variables)]
+    fn build(&self, app: &mut App) {
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Moves>();
+        }
+    }
+}
+
+/// Where to move, with a length up to 1.0 for full speed, so diagonals are no faster than
+/// straight lines and analog sticks can move slowly.
+#[derive(Component, Default)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Moves {
+    pub(crate) vector: Vec2,
+}
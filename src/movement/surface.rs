@@ -0,0 +1,127 @@
+use anyhow::bail;
+use bevy::prelude::*;
+
+use crate::{
+    movement::locomotion::{LocomotionSystem, Stride},
+    tilemap::Tilemap,
+    timestep::FixedSystems,
+};
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_fixed_system(footing.before(LocomotionSystem::Stamina));
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Footing>();
+        }
+    }
+}
+
+/// What a tile is made of, changing how fast and how surely whoever stands on it moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) enum Surface {
+    #[default]
+    Ground,
+    Carpet,
+    Mud,
+    Ice,
+    Water,
+}
+
+/// The surface under an entity's feet.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Footing {
+    /// Where the feet are, relative to the entity.
+    pub(crate) anchor: Vec2,
+    surface: Surface,
+}
+
+impl Surface {
+    /// Reads the surface a tile is tagged with, e.g. `"ice"`.
+    pub(crate) fn parse(value: &str) -> anyhow::Result<Surface> {
+        match value.to_lowercase().as_str() {
+            "ground" => Ok(Surface::Ground),
+            "carpet" => Ok(Surface::Carpet),
+            "mud" => Ok(Surface::Mud),
+            "ice" => Ok(Surface::Ice),
+            "water" => Ok(Surface::Water),
+            _ => bail!("unknown surface {value:?}"),
+        }
+    }
+
+    /// Multiplies the top speed of every gait.
+    fn speed(self) -> f32 {
+        match self {
+            Surface::Ground => 1.0,
+            Surface::Carpet => 0.9,
+            Surface::Mud => 0.5,
+            Surface::Ice => 1.2,
+            Surface::Water => 0.6,
+        }
+    }
+
+    /// Multiplies how quickly speed builds up and drops, so low traction slides.
+    pub(crate) fn traction(self) -> f32 {
+        match self {
+            Surface::Ground => 1.0,
+            Surface::Carpet => 1.25,
+            Surface::Mud => 0.6,
+            Surface::Ice => 0.1,
+            Surface::Water => 0.5,
+        }
+    }
+
+    pub(crate) fn apply(self, stride: Stride) -> Stride {
+        Stride {
+            max_speed: stride.max_speed * self.speed(),
+            acceleration: stride.acceleration * self.traction(),
+            deceleration: stride.deceleration * self.traction(),
+        }
+    }
+}
+
+impl Footing {
+    pub(crate) fn new(anchor: Vec2) -> Footing {
+        Footing {
+            anchor,
+            surface: Surface::Ground,
+        }
+    }
+
+    pub(crate) fn surface(&self) -> Surface {
+        self.surface
+    }
+}
+
+/// Looks up the tile under each entity's feet, in the topmost tilemap that has one. Tiles tagged
+/// with anything but a surface are ground.
+#[allow(clippy::needless_pass_by_value)]
+fn footing(
+    query_tilemap: Query<(&Tilemap, &GlobalTransform)>,
+    mut query: Query<(&mut Footing, &Transform)>,
+) {
+    let mut tilemaps = query_tilemap.iter().collect::<Vec<_>>();
+    tilemaps.sort_by(|(_, a), (_, b)| b.translation.z.total_cmp(&a.translation.z));
+
+    for (mut footing, transform) in query.iter_mut() {
+        let position = transform.translation.truncate() + footing.anchor;
+
+        let surface = tilemaps
+            .iter()
+            .find_map(|(tilemap, transform)| {
+                tilemap.tag(position - transform.translation.truncate())
+            })
+            .and_then(|tag| Surface::parse(tag).ok())
+            .unwrap_or_default();
+
+        if footing.surface != surface {
+            footing.surface = surface;
+        }
+    }
+}
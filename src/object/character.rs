@@ -2,22 +2,27 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    animation,
-    movement::{faces, runs, walks},
-    player,
+    animation, atlas,
+    input::{Action, Source},
+    movement::{dodge, faces, force, grid, locomotion, moves, surface},
+    player, timestep,
     visibility::z_index,
 };
 
 const DIMENSION: f32 = 48.0;
 const PADDING: f32 = 0.1;
+/// Tints a character that is out of breath.
+const EXHAUSTED: Color = Color::rgb(0.7, 0.75, 0.9);
 
 pub(crate) struct Plugin;
 
 impl bevy::prelude::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_startup_system(setup)
-            .add_system(keyboard_input)
-            .add_system(animate);
+            .add_system(action_input)
+            .add_system(exhaustion)
+            .add_system(footstep.after(animation::AnimationSystem::Play))
+            .add_system(join);
 
         #[cfg(feature = "editor")]
         {
@@ -32,161 +37,270 @@ impl bevy::prelude::Plugin for Plugin {
 pub(crate) struct Character;
 
 #[allow(clippy::needless_pass_by_value)]
-fn animate(
+fn action_input(
     mut query: Query<(
-        &Character,
-        &faces::Faces,
-        &walks::Walks,
-        &runs::Runs,
-        &mut animation::AnimationIndex,
-        &mut animation::AnimationTimer,
+        &player::Player,
+        &mut faces::Faces,
+        &mut moves::Moves,
+        &mut locomotion::Locomotion,
+        &mut dodge::Dodge,
     )>,
 ) {
-    for (_, faces, walks, runs, mut animation_index, mut animation_timer) in query.iter_mut() {
-        match (faces.direction, walks.walking || runs.running) {
-            (faces::Direction::Down, true) => animation_index.change(130, 6),
-            (faces::Direction::Down, false) => animation_index.change(74, 6),
-            (faces::Direction::Left, true) => animation_index.change(124, 6),
-            (faces::Direction::Left, false) => animation_index.change(68, 6),
-            (faces::Direction::Right, true) => animation_index.change(112, 6),
-            (faces::Direction::Right, false) => animation_index.change(56, 6),
-            (faces::Direction::Up, true) => animation_index.change(118, 6),
-            (faces::Direction::Up, false) => animation_index.change(62, 6),
-        }
+    for (player, mut faces, mut moves, mut locomotion, mut dodge) in query.iter_mut() {
+        let actions = player.actions();
 
-        if runs.running && (animation_timer.duration() - 0.1).abs() > f32::EPSILON {
-            *animation_timer = animation::AnimationTimer::new(Timer::from_seconds(0.1, true));
+        // a dodge keeps going the way it started
+        if !dodge.dodging() {
+            faces.direction = faces.direction.towards(player.movement());
         }
-        if !runs.running && (animation_timer.duration() - 0.2).abs() > f32::EPSILON {
-            *animation_timer = animation::AnimationTimer::new(Timer::from_seconds(0.2, true));
+        if actions.just_pressed(Action::Dodge) {
+            dodge.start();
         }
+        moves.vector = player.movement();
+        locomotion.gait = if actions.pressed(Action::Run) {
+            locomotion::Gait::Run
+        } else if actions.pressed(Action::Sneak) {
+            locomotion::Gait::Sneak
+        } else {
+            locomotion::Gait::Walk
+        };
     }
 }
 
+/// Shows which characters are too exhausted to run.
 #[allow(clippy::needless_pass_by_value)]
-fn keyboard_input(
-    mut query: Query<(
-        &player::Player,
-        &mut faces::Faces,
-        &mut walks::Walks,
-        &mut runs::Runs,
-    )>,
+fn exhaustion(
+    mut exhausted_events: EventReader<locomotion::ExhaustedEvent>,
+    mut recovered_events: EventReader<locomotion::RecoveredEvent>,
+    query_character: Query<&Children, With<Character>>,
+    mut query: Query<&mut TextureAtlasSprite>,
 ) {
-    for (player, mut faces, mut walks, mut runs) in query.iter_mut() {
-        let keys = player.keys();
+    let tints = exhausted_events
+        .iter()
+        .map(|event| (event.entity, EXHAUSTED))
+        .chain(
+            recovered_events
+                .iter()
+                .map(|event| (event.entity, Color::WHITE)),
+        );
 
-        // pressed directional
-        if keys.just_pressed(KeyCode::W) {
-            faces.direction = faces::Direction::Up;
-        }
-        if keys.just_pressed(KeyCode::A) {
-            faces.direction = faces::Direction::Left;
-        }
-        if keys.just_pressed(KeyCode::S) {
-            faces.direction = faces::Direction::Down;
-        }
-        if keys.just_pressed(KeyCode::D) {
-            faces.direction = faces::Direction::Right;
-        }
+    for (entity, tint) in tints {
+        let Ok(children) = query_character.get(entity) else {
+            continue;
+        };
 
-        // pressed directional
-        if keys.any_just_pressed([KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D]) {
-            if keys.pressed(KeyCode::LShift) {
-                runs.running = true;
-                walks.walking = false;
-            } else {
-                runs.running = false;
-                walks.walking = true;
+        for child in children.iter() {
+            if let Ok(mut sprite) = query.get_mut(*child) {
+                sprite.color = tint;
             }
         }
+    }
+}
 
-        // released directional
-        if keys.just_released(KeyCode::W) && faces.direction == faces::Direction::Up {
-            runs.running = false;
-            walks.walking = false;
-        }
-        if keys.just_released(KeyCode::A) && faces.direction == faces::Direction::Left {
-            runs.running = false;
-            walks.walking = false;
-        }
-        if keys.just_released(KeyCode::S) && faces.direction == faces::Direction::Down {
-            runs.running = false;
-            walks.walking = false;
-        }
-        if keys.just_released(KeyCode::D) && faces.direction == faces::Direction::Right {
-            runs.running = false;
-            walks.walking = false;
+/// Where footstep sounds hook in, on whatever surface the foot came down on.
+#[allow(clippy::needless_pass_by_value)]
+fn footstep(
+    mut animation_events: EventReader<animation::AnimationEvent>,
+    query_sprite: Query<&Parent>,
+    query: Query<&surface::Footing, With<Character>>,
+) {
+    for event in animation_events.iter() {
+        if event.name != "footstep" {
+            continue;
         }
 
-        // pressed LShift
-        if keys.just_pressed(KeyCode::LShift)
-            && keys.any_pressed([KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D])
-        {
-            runs.running = true;
-            walks.walking = false;
-        }
+        // the sprite is animated, the character it draws has the feet
+        let Ok(parent) = query_sprite.get(event.entity) else {
+            continue;
+        };
 
-        // released LShift
-        if keys.just_released(KeyCode::LShift)
-            && keys.any_pressed([KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D])
-        {
-            runs.running = false;
-            walks.walking = true;
+        if let Ok(footing) = query.get(parent.0) {
+            debug!("{:?} stepped on {:?}", parent.0, footing.surface());
         }
     }
 }
 
+fn state_machine() -> animation::state_machine::StateMachine {
+    use animation::state_machine::{Condition, State, StateMachine, Transition};
+
+    let directions = [
+        (faces::Direction::Down, "down"),
+        (faces::Direction::Left, "left"),
+        (faces::Direction::Right, "right"),
+        (faces::Direction::Up, "up"),
+    ];
+
+    directions.into_iter().fold(
+        StateMachine::default(),
+        |state_machine, (direction, name)| {
+            let idle = format!("idle_{name}");
+            let walk = format!("walk_{name}");
+            let run = format!("run_{name}");
+            let sneak = format!("sneak_{name}");
+            let dodge = format!("dodge_{name}");
+
+            // nothing else plays until a dodge is over
+            let unless_dodging = |condition| {
+                Condition::All(vec![
+                    Condition::Faces(direction),
+                    Condition::Dodging(false),
+                    condition,
+                ])
+            };
+
+            state_machine
+                .with_state(State::new(&idle, &idle))
+                .with_state(State::new(&walk, &walk))
+                .with_state(State::new(&run, &walk).with_speed(2.0))
+                .with_state(State::new(&sneak, &walk).with_speed(0.5))
+                .with_state(State::new(&dodge, &dodge))
+                .with_transition(Transition::any(
+                    dodge,
+                    Condition::All(vec![Condition::Faces(direction), Condition::Dodging(true)]),
+                ))
+                .with_transition(Transition::any(
+                    run,
+                    unless_dodging(Condition::Gait(locomotion::Gait::Run)),
+                ))
+                .with_transition(Transition::any(
+                    walk,
+                    unless_dodging(Condition::Gait(locomotion::Gait::Walk)),
+                ))
+                .with_transition(Transition::any(
+                    sneak,
+                    unless_dodging(Condition::Gait(locomotion::Gait::Sneak)),
+                ))
+                .with_transition(Transition::any(
+                    idle,
+                    unless_dodging(Condition::Moving(false)),
+                ))
+        },
+    )
+}
+
 #[allow(clippy::needless_pass_by_value)]
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    mut atlases: ResMut<atlas::Atlases>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
-    let animation_index = animation::AnimationIndex::new(74, 6);
-    let animation_timer = animation::AnimationTimer::new(Timer::from_seconds(0.2, true));
+    spawn(
+        &mut commands,
+        &asset_server,
+        &mut atlases,
+        &mut texture_atlases,
+        player::Player::new(0, Source::Any),
+        Vec2::ZERO,
+    );
+}
 
-    let player = player::Player::default();
+/// Spawns a character for each player that joins, in a row beside the first player.
+#[allow(clippy::cast_precision_loss, clippy::needless_pass_by_value)]
+fn join(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<atlas::Atlases>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut join_events: EventReader<player::JoinEvent>,
+    query: Query<(&player::Player, &GlobalTransform)>,
+) {
+    let position = query
+        .iter()
+        .min_by_key(|(player, _)| player.slot())
+        .map_or(Vec2::ZERO, |(_, transform)| {
+            transform.translation.truncate()
+        });
+
+    for event in join_events.iter() {
+        spawn(
+            &mut commands,
+            &asset_server,
+            &mut atlases,
+            &mut texture_atlases,
+            player::Player::new(event.slot, event.source),
+            position + Vec2::new(DIMENSION * event.slot as f32, 0.0),
+        );
+    }
+}
 
-    let texture_handle = asset_server.load("character/04_48x48.png");
-    let texture_atlas = TextureAtlas::from_grid_with_padding(
-        texture_handle,
-        Vec2::new(DIMENSION - PADDING, DIMENSION * 2.0 - PADDING),
-        56,
-        20,
-        Vec2::new(PADDING, PADDING),
+fn spawn(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    atlases: &mut atlas::Atlases,
+    texture_atlases: &mut Assets<TextureAtlas>,
+    player: player::Player,
+    position: Vec2,
+) {
+    let animation_clip = animation::AnimationClip::new(
+        asset_server.load("character/04_48x48.clips.ron"),
+        "idle_down",
     );
-    let texture_atlas_handle = texture_atlases.add(texture_atlas);
+    let animation_index = animation::AnimationIndex::default();
+    let animation_state_machine = state_machine();
+    let animation_timer = animation::AnimationTimer::new(Timer::from_seconds(0.2, true));
 
-    let mut entity = commands.spawn_bundle(SpriteSheetBundle {
-        texture_atlas: texture_atlas_handle,
-        sprite: TextureAtlasSprite {
-            index: animation_index.start(),
-            ..Default::default()
+    let texture_atlas_handle = atlases.grid(
+        asset_server,
+        texture_atlases,
+        "character/04_48x48.png",
+        atlas::Grid {
+            tile_size: Vec2::new(DIMENSION - PADDING, DIMENSION * 2.0 - PADDING),
+            columns: 56,
+            rows: 20,
+            padding: Vec2::new(PADDING, PADDING),
         },
-        ..Default::default()
-    });
+    );
+
+    let mut entity = commands.spawn_bundle(TransformBundle::from(Transform::from_translation(
+        position.extend(0.0),
+    )));
 
     // identity
-    entity.insert(Name::new("character_04")).insert(Character);
+    entity
+        .insert(Name::new(format!(
+            "character_04 (player {})",
+            player.slot() + 1
+        )))
+        .insert(Character);
 
-    // animation
+    // movement
     entity
-        .insert(animation_index)
-        .insert(animation_timer)
         .insert(faces::Faces {
             direction: faces::Direction::Down,
         })
-        .insert(runs::Runs {
-            strength: 48.0,
-            running: false,
-        })
-        .insert(walks::Walks {
-            strength: 24.0,
-            walking: false,
-        });
+        .insert(moves::Moves::default())
+        .insert(locomotion::Locomotion::default())
+        .insert(locomotion::Stamina::default())
+        .insert(grid::Steps::new(
+            DIMENSION,
+            Vec2::new(0.0, -DIMENSION / 2.0),
+        ))
+        .insert(surface::Footing::new(Vec2::new(0.0, -DIMENSION / 2.0)))
+        .insert(dodge::Dodge::default())
+        .insert(force::Forces::default());
 
     // visibility
-    entity.insert(z_index::ZIndex::new(5.0));
+    entity
+        .insert(z_index::ZIndex::new(5.0))
+        .insert(timestep::Interpolation::new(position))
+        .with_children(|children| {
+            // drawn apart from the body, so drawing between steps does not move it
+            let mut sprite = children.spawn_bundle(SpriteSheetBundle {
+                texture_atlas: texture_atlas_handle,
+                ..Default::default()
+            });
+            sprite
+                .insert(Name::new("sprite"))
+                .insert(timestep::Interpolated);
+
+            // animation
+            sprite
+                .insert(animation_clip)
+                .insert(animation_index)
+                .insert(animation_state_machine)
+                .insert(animation_timer);
+        });
 
     // control
     entity.insert(player);
@@ -196,14 +310,7 @@ fn setup(
         .insert(RigidBody::Dynamic)
         .insert(GravityScale(0.0))
         .insert(LockedAxes::ROTATION_LOCKED)
-        .insert(ExternalImpulse {
-            impulse: Vec2::ZERO,
-            torque_impulse: 0.0,
-        })
-        .insert(Damping {
-            linear_damping: 18.0,
-            angular_damping: 1.0,
-        })
+        .insert(Velocity::zero())
         .with_children(|children| {
             children
                 .spawn()
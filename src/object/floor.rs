@@ -1,17 +1,44 @@
 use bevy::prelude::*;
 
-use crate::visibility::z_index;
+#[cfg(feature = "editor")]
+use bevy::render::camera::Camera2d;
+
+#[cfg(feature = "editor")]
+use crate::camera;
+use crate::{
+    atlas,
+    tilemap::{self, autotile::Autotile},
+};
 
 const DIMENSION: f32 = 48.0;
 const PADDING: f32 = 0.1;
+/// Floors are flat, so the whole tilemap sits at one depth instead of being layered by `ZIndex`.
+const Z_INDEX: f32 = 4.0;
+/// How far above the one below each layer of a map is drawn.
+const LAYER_SPACING: f32 = 0.1;
+
+pub(crate) const TEXTURE: &str = "room/builder_48x48.png";
+const RULES: &str = "room/builder_48x48.autotile.ron";
+/// The terrain the editor paints with.
+#[cfg(feature = "editor")]
+const BRUSH: &str = "wooden floor";
 
 pub(crate) struct Plugin;
 
 impl bevy::prelude::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<SpawnEvent>()
+        app.add_event::<EraseEvent>()
+            .add_event::<PaintEvent>()
+            .add_event::<LayerEvent>()
             .add_startup_system(setup)
-            .add_system(spawn);
+            .add_system(erase)
+            .add_system(paint)
+            .add_system(layer);
+
+        #[cfg(feature = "editor")]
+        {
+            app.add_system(brush.before(erase).before(paint));
+        }
     }
 }
 
@@ -19,55 +46,172 @@ impl bevy::prelude::Plugin for Plugin {
 #[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
 pub(crate) struct Floor;
 
-struct SpawnEvent {
-    x: f32,
-    y: f32,
+/// Paints a terrain onto the floor, leaving the choice of tile to the autotile rules.
+pub(crate) struct PaintEvent {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) terrain: String,
+    pub(crate) room: Option<Entity>,
 }
 
-#[allow(clippy::cast_precision_loss, clippy::needless_pass_by_value)]
-fn spawn(
-    mut event_reader: EventReader<SpawnEvent>,
+/// Erases the terrain painted onto the floor, so the autotile rules pick the neighbours again.
+#[cfg_attr(not(feature = "editor"), allow(dead_code))]
+pub(crate) struct EraseEvent {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+}
+
+/// Lays a layer of a map over the floor, as a tilemap that is a child of the room.
+pub(crate) struct LayerEvent {
+    pub(crate) room: Entity,
+    pub(crate) name: String,
+    /// How many layers of the map are below this one.
+    pub(crate) depth: usize,
+    /// Centres relative to the room, and indices in the atlas.
+    pub(crate) tiles: Vec<(Vec2, usize)>,
+    /// Tags of the tiles at each index in the atlas.
+    pub(crate) tags: Vec<(usize, String)>,
+}
+
+fn grid() -> atlas::Grid {
+    atlas::Grid {
+        tile_size: Vec2::new(DIMENSION - PADDING, DIMENSION - PADDING),
+        columns: 76,
+        rows: 109,
+        padding: Vec2::new(PADDING, PADDING),
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    mut atlases: ResMut<atlas::Atlases>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
+    let texture_atlas_handle = atlases.grid(&asset_server, &mut texture_atlases, TEXTURE, grid());
+
+    let mut entity = commands.spawn_bundle(TransformBundle::from(Transform::from_xyz(
+        0.0, 0.0, Z_INDEX,
+    )));
+
+    // identity
+    entity.insert(Name::new("floor")).insert(Floor);
+
+    // visibility
+    entity
+        .insert(tilemap::Tilemap::new(
+            texture_atlas_handle,
+            Vec2::new(DIMENSION, DIMENSION),
+        ))
+        .insert(Autotile::new(asset_server.load(RULES)));
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn paint(
+    mut event_reader: EventReader<PaintEvent>,
+    mut query: Query<(&tilemap::Tilemap, &mut Autotile, &Transform), With<Floor>>,
+) {
+    let Ok((tilemap, mut autotile, transform)) = query.get_single_mut() else {
+        return;
+    };
+
     for event in event_reader.iter() {
-        let texture_handle = asset_server.load("room/builder_48x48.png");
-        let texture_atlas = TextureAtlas::from_grid_with_padding(
-            texture_handle,
-            Vec2::new(DIMENSION - PADDING, DIMENSION - PADDING),
-            76,
-            109,
-            Vec2::new(PADDING, PADDING),
-        );
-        let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-        let mut entity = commands.spawn_bundle(SpriteSheetBundle {
-            texture_atlas: texture_atlas_handle,
-            sprite: TextureAtlasSprite {
-                index: 2619,
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(event.x, event.y, 0.0),
-            ..Default::default()
-        });
+        let cell = tilemap.cell(Vec2::new(event.x, event.y) - transform.translation.truncate());
+        autotile.paint(cell, &event.terrain, event.room);
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn erase(
+    mut event_reader: EventReader<EraseEvent>,
+    mut query: Query<(&tilemap::Tilemap, &mut Autotile, &Transform), With<Floor>>,
+) {
+    let Ok((tilemap, mut autotile, transform)) = query.get_single_mut() else {
+        return;
+    };
+
+    for event in event_reader.iter() {
+        let cell = tilemap.cell(Vec2::new(event.x, event.y) - transform.translation.truncate());
+        autotile.erase(cell);
+    }
+}
+
+/// Paints terrain under the cursor with the left mouse button and erases it with the right, while
+/// Ctrl is held.
+#[cfg(feature = "editor")]
+#[allow(clippy::needless_pass_by_value)]
+fn brush(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    pixel_perfect: Res<camera::pixel_perfect::PixelPerfect>,
+    query_camera: Query<(&GlobalTransform, &OrthographicProjection), With<Camera2d>>,
+    mut erase_events: EventWriter<EraseEvent>,
+    mut paint_events: EventWriter<PaintEvent>,
+) {
+    if !keys.any_pressed([KeyCode::LControl, KeyCode::RControl]) {
+        return;
+    }
 
-        // identity
-        entity.insert(Name::new("floor")).insert(Floor);
+    let (Some(window), Ok((transform, projection))) =
+        (windows.get_primary(), query_camera.get_single())
+    else {
+        return;
+    };
+    let Some(position) = camera::cursor_position(window, &pixel_perfect, transform, projection)
+    else {
+        return;
+    };
 
-        // visibility
-        entity.insert(z_index::ZIndex::new(4.0));
+    if buttons.pressed(MouseButton::Left) {
+        paint_events.send(PaintEvent {
+            x: position.x,
+            y: position.y,
+            terrain: BRUSH.to_string(),
+            room: None,
+        });
+    } else if buttons.pressed(MouseButton::Right) {
+        erase_events.send(EraseEvent {
+            x: position.x,
+            y: position.y,
+        });
     }
 }
 
 #[allow(clippy::cast_precision_loss, clippy::needless_pass_by_value)]
-fn setup(mut event_writer: EventWriter<SpawnEvent>) {
-    for y in -4..6 {
-        for x in -6..12 {
-            event_writer.send(SpawnEvent {
-                x: x as f32 * DIMENSION,
-                y: y as f32 * DIMENSION,
-            });
+fn layer(
+    mut event_reader: EventReader<LayerEvent>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<atlas::Atlases>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    for event in event_reader.iter() {
+        let texture_atlas_handle =
+            atlases.grid(&asset_server, &mut texture_atlases, TEXTURE, grid());
+
+        let mut tilemap =
+            tilemap::Tilemap::new(texture_atlas_handle, Vec2::new(DIMENSION, DIMENSION));
+        for (position, index) in &event.tiles {
+            tilemap.insert(
+                *position,
+                tilemap::Tile {
+                    index: *index,
+                    owner: None,
+                },
+            );
         }
+        for (index, tag) in &event.tags {
+            tilemap.set_tag(*index, tag);
+        }
+
+        let z = Z_INDEX + LAYER_SPACING * (event.depth + 1) as f32;
+        let entity = commands
+            .spawn_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.0, z)))
+            .insert(Name::new(format!("floor layer ({})", event.name)))
+            .insert(tilemap)
+            .id();
+        commands.entity(event.room).add_child(entity);
     }
 }
@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    map,
+    movement::force::{self, ForceSystem},
+    physics,
+};
+
+/// In pixels per second.
+const KNOCKBACK: f32 = 320.0;
+/// In seconds.
+const LOCK: f32 = 0.2;
+const PUSH: f32 = 960.0;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CreateRequestEvent>()
+            .add_system(sensor)
+            .add_system(push.after(sensor).before(ForceSystem))
+            .add_system(spawn);
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Hazard>();
+        }
+    }
+}
+
+/// A sensor that knocks back what enters it, and keeps pushing it out while it stays.
+#[derive(Component, Default)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Hazard {
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    inside: Vec<Entity>,
+}
+
+pub(crate) struct CreateRequestEvent {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) collider: Collider,
+    pub(crate) room: Option<Entity>,
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn sensor(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut impulse_events: EventWriter<force::ImpulseEvent>,
+    query_body: Query<(Option<&Parent>, Option<&force::Forces>)>,
+    query_forces: Query<&GlobalTransform, With<force::Forces>>,
+    mut query: Query<(&mut Hazard, &GlobalTransform)>,
+) {
+    let body = |collider: Entity| match query_body.get(collider) {
+        Ok((_, Some(_))) => Some(collider),
+        Ok((Some(parent), None)) => query_forces.get(parent.0).ok().map(|_| parent.0),
+        _ => None,
+    };
+
+    for collision_event in collision_events.iter() {
+        let (started, a, b) = match collision_event {
+            CollisionEvent::Started(a, b, _) => (true, *a, *b),
+            CollisionEvent::Stopped(a, b, _) => (false, *a, *b),
+        };
+
+        for (hazard, other) in [(a, b), (b, a)] {
+            let Ok((mut hazard, hazard_transform)) = query.get_mut(hazard) else {
+                continue;
+            };
+            let Some(entity) = body(other) else {
+                continue;
+            };
+
+            if !started {
+                hazard.inside.retain(|inside| *inside != entity);
+                continue;
+            }
+
+            hazard.inside.push(entity);
+            if let Ok(transform) = query_forces.get(entity) {
+                let away = (transform.translation - hazard_transform.translation)
+                    .truncate()
+                    .normalize_or_zero();
+                impulse_events.send(force::ImpulseEvent {
+                    entity,
+                    impulse: away * KNOCKBACK,
+                    lock: Some(LOCK),
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn push(
+    mut force_events: EventWriter<force::ForceEvent>,
+    query_forces: Query<&GlobalTransform, With<force::Forces>>,
+    query: Query<(&Hazard, &GlobalTransform)>,
+) {
+    for (hazard, hazard_transform) in query.iter() {
+        for entity in &hazard.inside {
+            if let Ok(transform) = query_forces.get(*entity) {
+                let away = (transform.translation - hazard_transform.translation)
+                    .truncate()
+                    .normalize_or_zero();
+                force_events.send(force::ForceEvent {
+                    entity: *entity,
+                    force: away * PUSH,
+                });
+            }
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+fn spawn(mut event_reader: EventReader<CreateRequestEvent>, mut commands: Commands) {
+    for event in event_reader.iter() {
+        let mut entity = commands.spawn_bundle(TransformBundle::from(Transform::from_xyz(
+            event.x, event.y, 0.0,
+        )));
+
+        // identity
+        entity.insert(Name::new("hazard")).insert(Hazard::default());
+        if let Some(room) = event.room {
+            entity.insert(map::Member(room));
+        }
+
+        // physics
+        entity
+            .insert(event.collider.clone())
+            .insert(Sensor)
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(CollisionGroups::new(physics::HOSTILE, physics::ALL_GROUPS));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+
+    use super::*;
+
+    #[test]
+    fn pushes_out_what_stays_inside() {
+        let mut world = World::new();
+        world.insert_resource(Events::<force::ForceEvent>::default());
+
+        let body = world
+            .spawn()
+            .insert(force::Forces::default())
+            .insert(GlobalTransform::from_xyz(-48.0, 0.0, 0.0))
+            .id();
+        world
+            .spawn()
+            .insert(Hazard { inside: vec![body] })
+            .insert(GlobalTransform::default());
+
+        SystemStage::single_threaded()
+            .with_system(push)
+            .run(&mut world);
+
+        let events = world.get_resource::<Events<force::ForceEvent>>().unwrap();
+        let mut reader = events.get_reader();
+        let pushes = reader.iter(events).collect::<Vec<_>>();
+        assert_eq!(pushes.len(), 1);
+        assert_eq!(pushes[0].entity, body);
+        assert_eq!(pushes[0].force, Vec2::new(-PUSH, 0.0));
+    }
+}
@@ -1,10 +1,9 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::visibility::z_index;
+use crate::{map, visibility::z_index};
 
 const DIMENSION: f32 = 48.0;
-const PADDING: f32 = 0.1;
 const MAX_REFLECTION_STRENGTH: usize = 7;
 
 pub(crate) struct Plugin;
@@ -12,7 +11,6 @@ pub(crate) struct Plugin;
 impl bevy::prelude::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_event::<CreateRequestEvent>()
-            .add_startup_system(setup)
             .add_system(animate)
             .add_system(sensor)
             .add_system(spawn);
@@ -79,9 +77,10 @@ fn sensor(
     }
 }
 
-struct CreateRequestEvent {
-    x: f32,
-    y: f32,
+pub(crate) struct CreateRequestEvent {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) room: Option<Entity>,
 }
 
 #[allow(clippy::cast_precision_loss, clippy::needless_pass_by_value)]
@@ -89,18 +88,9 @@ fn spawn(
     mut event_reader: EventReader<CreateRequestEvent>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
 ) {
     for event in event_reader.iter() {
-        let texture_handle = asset_server.load("mirror/01_48x48.png");
-        let texture_atlas = TextureAtlas::from_grid_with_padding(
-            texture_handle,
-            Vec2::new(DIMENSION - PADDING, DIMENSION * 2.0 - PADDING),
-            12,
-            1,
-            Vec2::new(PADDING, PADDING),
-        );
-        let texture_atlas_handle = texture_atlases.add(texture_atlas);
+        let texture_atlas_handle = asset_server.load("mirror/01_48x48.aseprite.json");
 
         let mut entity = commands.spawn_bundle(SpriteSheetBundle {
             texture_atlas: texture_atlas_handle,
@@ -113,6 +103,9 @@ fn spawn(
 
         // identity
         entity.insert(Name::new("mirror")).insert(Mirror);
+        if let Some(room) = event.room {
+            entity.insert(map::Member(room));
+        }
 
         // animation
         entity.insert(ReflectionStrengths([false; MAX_REFLECTION_STRENGTH]));
@@ -151,15 +144,3 @@ fn spawn(
         });
     }
 }
-
-#[allow(clippy::cast_precision_loss, clippy::needless_pass_by_value)]
-fn setup(mut event_writer: EventWriter<CreateRequestEvent>) {
-    for y in 0..2 {
-        for x in 0..8 {
-            event_writer.send(CreateRequestEvent {
-                x: x as f32 * DIMENSION,
-                y: y as f32 * DIMENSION * 2.0,
-            });
-        }
-    }
-}
@@ -1,3 +1,4 @@
 pub(crate) mod character;
 pub(crate) mod floor;
+pub(crate) mod hazard;
 pub(crate) mod mirror;
@@ -1,11 +1,49 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use crate::timestep::{self, FixedStage, FixedUpdate};
+
+pub(crate) const ALL_GROUPS: u32 = u32::MAX;
+/// Sensors that harm whatever enters them. They belong to no other group, so filtering it out
+/// only ignores them.
+pub(crate) const HOSTILE: u32 = 1 << 1;
+
 pub(crate) struct Plugin;
 
 impl bevy::prelude::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(48.0));
+        let systems = RapierPhysicsPlugin::<NoUserData>::get_systems;
+
+        // physics steps along with gameplay, between the stages of `FixedUpdate`
+        app.add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(48.0)
+                .with_default_system_setup(false),
+        )
+        .add_startup_system(setup)
+        .stage(FixedUpdate, |schedule: &mut Schedule| {
+            schedule
+                .add_stage_after(
+                    FixedStage::Update,
+                    PhysicsStages::SyncBackend,
+                    SystemStage::parallel().with_system_set(systems(PhysicsStages::SyncBackend)),
+                )
+                .add_stage_after(
+                    PhysicsStages::SyncBackend,
+                    PhysicsStages::StepSimulation,
+                    SystemStage::parallel().with_system_set(systems(PhysicsStages::StepSimulation)),
+                )
+                .add_stage_after(
+                    PhysicsStages::StepSimulation,
+                    PhysicsStages::Writeback,
+                    SystemStage::parallel().with_system_set(systems(PhysicsStages::Writeback)),
+                )
+        })
+        // despawns are only noticed during the frame they happen in, which may not have a step
+        .add_stage_before(
+            CoreStage::Last,
+            PhysicsStages::DetectDespawn,
+            SystemStage::parallel().with_system_set(systems(PhysicsStages::DetectDespawn)),
+        );
 
         #[cfg(feature = "editor")]
         {
@@ -13,3 +51,11 @@ impl bevy::prelude::Plugin for Plugin {
         }
     }
 }
+
+/// Steps exactly once per step of `FixedUpdate`.
+fn setup(mut rapier_configuration: ResMut<RapierConfiguration>) {
+    rapier_configuration.timestep_mode = TimestepMode::Fixed {
+        dt: timestep::DT,
+        substeps: 1,
+    };
+}
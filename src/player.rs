@@ -1,10 +1,19 @@
+use std::collections::HashSet;
+
 use bevy::prelude::*;
 
+use crate::{
+    input::{Action, ActionSystem, Controls, Source},
+    replay::Playback,
+};
+
 pub(crate) struct Plugin;
 
 impl bevy::prelude::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_system(keyboard_input);
+        app.add_event::<JoinEvent>()
+            .add_system_to_stage(CoreStage::PreUpdate, action_input.after(ActionSystem))
+            .add_system_to_stage(CoreStage::PreUpdate, join.after(ActionSystem));
 
         #[cfg(feature = "editor")]
         {
@@ -14,23 +23,113 @@ impl bevy::prelude::Plugin for Plugin {
     }
 }
 
-#[derive(Component, Default)]
+/// Someone pressed [`Action::Join`] on a source no player is using.
+pub(crate) struct JoinEvent {
+    pub(crate) slot: usize,
+    pub(crate) source: Source,
+}
+
+#[derive(Component)]
 #[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
 pub(crate) struct Player {
+    /// Starts from 0 in the order players joined.
+    slot: usize,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    source: Source,
     #[cfg_attr(feature = "editor", inspectable(ignore))]
-    keys: Input<KeyCode>,
+    actions: Input<Action>,
+    movement: Vec2,
 }
 
 impl Player {
-    pub(crate) fn keys(&self) -> &Input<KeyCode> {
-        &self.keys
+    pub(crate) fn new(slot: usize, source: Source) -> Player {
+        Player {
+            slot,
+            source,
+            actions: Input::default(),
+            movement: Vec2::ZERO,
+        }
+    }
+
+    pub(crate) fn slot(&self) -> usize {
+        self.slot
+    }
+
+    pub(crate) fn actions(&self) -> &Input<Action> {
+        &self.actions
+    }
+
+    /// Where the player wants to move, with a length up to 1.0.
+    pub(crate) fn movement(&self) -> Vec2 {
+        self.movement
     }
 }
 
-fn keyboard_input(keys: Res<Input<KeyCode>>, mut query: Query<&mut Player>) {
-    let keys = keys.into_inner();
+/// Routes each player's source to it, or what they did in the recording being replayed.
+#[allow(clippy::needless_pass_by_value)]
+fn action_input(
+    controls: Controls,
+    playback: Option<Res<Playback>>,
+    mut query: Query<&mut Player>,
+) {
+    if let Some(playback) = playback {
+        for mut player in query.iter_mut() {
+            let slot = player.slot;
+            crate::input::update(&mut player.actions, playback.actions(slot));
+            player.movement = playback.movement(slot);
+        }
+        return;
+    }
+
+    let claimed = query
+        .iter()
+        .map(|player| player.source)
+        .filter(|source| *source != Source::Any)
+        .collect::<Vec<_>>();
 
     for mut player in query.iter_mut() {
-        player.keys = keys.clone();
+        let pressed = controls.pressed(player.source, &claimed);
+        crate::input::update(&mut player.actions, &pressed);
+        player.movement = controls.movement(player.source, &claimed);
+    }
+}
+
+/// Watches the sources no player has claimed for someone pressing join.
+#[allow(clippy::needless_pass_by_value)]
+fn join(
+    controls: Controls,
+    playback: Option<Res<Playback>>,
+    gamepads: Res<Gamepads>,
+    mut held: Local<HashSet<Source>>,
+    mut join_events: EventWriter<JoinEvent>,
+    query: Query<&Player>,
+) {
+    // players join when they did in the recording instead
+    if playback.is_some() {
+        return;
     }
+
+    let claimed = query.iter().map(|player| player.source).collect::<Vec<_>>();
+    let slot = query
+        .iter()
+        .map(|player| player.slot + 1)
+        .max()
+        .unwrap_or(0);
+
+    let pressed = Source::connected(&gamepads)
+        .into_iter()
+        .filter(|source| !claimed.contains(source))
+        .filter(|source| controls.pressed(*source, &[]).contains(&Action::Join))
+        .collect::<Vec<_>>();
+
+    // sources joining in the same frame get their slots in a fixed order, so replays match
+    let joining = pressed.iter().filter(|source| !held.contains(source));
+    for (slot, source) in (slot..).zip(joining) {
+        join_events.send(JoinEvent {
+            slot,
+            source: *source,
+        });
+    }
+
+    *held = pressed.into_iter().collect();
 }
@@ -0,0 +1,222 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    input::{Action, ActionSystem, Source},
+    player, timestep,
+};
+
+/// Parsed from `--record <file>`, `--replay <file>` and `--headless`.
+#[derive(Clone, Default)]
+pub(crate) struct Options {
+    pub(crate) mode: Option<Mode>,
+    pub(crate) headless: bool,
+}
+
+#[derive(Clone)]
+pub(crate) enum Mode {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+/// What happened to the players during one frame. Recordings hold one per line.
+#[derive(Default, Deserialize, Serialize)]
+pub(crate) struct Frame {
+    joins: Vec<(usize, Source)>,
+    /// The actions held by each player slot, and where it moved.
+    players: Vec<(usize, Vec<Action>, Vec2)>,
+    /// Recordings from before there were steps took one a frame.
+    #[serde(default = "one")]
+    steps: u32,
+}
+
+pub(crate) struct Recording(BufWriter<File>);
+
+/// The frames being replayed. While present, players ignore their sources.
+pub(crate) struct Playback {
+    frames: Vec<Frame>,
+    frame: usize,
+}
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        let options = app
+            .world
+            .get_resource_or_insert_with(Options::default)
+            .clone();
+
+        let Some(mode) = options.mode else {
+            return;
+        };
+
+        match mode {
+            Mode::Record(path) => match File::create(&path) {
+                Ok(file) => {
+                    app.insert_resource(Recording(BufWriter::new(file)))
+                        .add_system_to_stage(CoreStage::Last, record)
+                        .add_system_to_stage(CoreStage::Last, report);
+                }
+                Err(error) => {
+                    error!("failed to record to {}: {}", path.display(), error);
+                }
+            },
+            Mode::Replay(path) => match read(&path) {
+                Ok(frames) => {
+                    app.insert_resource(Playback { frames, frame: 0 })
+                        .add_system_to_stage(CoreStage::PreUpdate, play.after(ActionSystem))
+                        .add_system_to_stage(CoreStage::Last, advance)
+                        .add_system_to_stage(CoreStage::Last, report.before(advance));
+                }
+                Err(error) => {
+                    error!("failed to replay {}: {}", path.display(), error);
+                }
+            },
+        }
+    }
+}
+
+impl Options {
+    pub(crate) fn from_args(args: impl IntoIterator<Item = String>) -> Options {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => options.mode = args.next().map(|path| Mode::Record(path.into())),
+                "--replay" => options.mode = args.next().map(|path| Mode::Replay(path.into())),
+                "--headless" => options.headless = true,
+                _ => {}
+            }
+        }
+
+        options
+    }
+}
+
+impl Playback {
+    pub(crate) fn actions(&self, slot: usize) -> &[Action] {
+        self.player(slot)
+            .map_or(&[], |(_, actions, _)| actions.as_slice())
+    }
+
+    pub(crate) fn movement(&self, slot: usize) -> Vec2 {
+        self.player(slot)
+            .map_or(Vec2::ZERO, |(_, _, movement)| *movement)
+    }
+
+    fn player(&self, slot: usize) -> Option<&(usize, Vec<Action>, Vec2)> {
+        self.frames
+            .get(self.frame)
+            .and_then(|frame| frame.players.iter().find(|(other, ..)| *other == slot))
+    }
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn read(path: &PathBuf) -> anyhow::Result<Vec<Frame>> {
+    let mut frames = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        frames.push(ron::from_str(&line?)?);
+    }
+    Ok(frames)
+}
+
+/// Writes out every frame as it is played, so a recording survives the game crashing.
+#[allow(clippy::needless_pass_by_value)]
+fn record(
+    timestep: Res<timestep::Timestep>,
+    mut recording: ResMut<Recording>,
+    mut join_events: EventReader<player::JoinEvent>,
+    query: Query<&player::Player>,
+) {
+    let mut players = query
+        .iter()
+        .map(|player| {
+            let mut actions = player.actions().get_pressed().copied().collect::<Vec<_>>();
+            actions.sort_by_key(|action| Action::ALL.iter().position(|other| other == action));
+            (player.slot(), actions, player.movement())
+        })
+        .collect::<Vec<_>>();
+    players.sort_by_key(|(slot, ..)| *slot);
+
+    let frame = Frame {
+        joins: join_events
+            .iter()
+            .map(|event| (event.slot, event.source))
+            .collect(),
+        players,
+        steps: timestep.steps(),
+    };
+
+    if let Err(error) = write(&mut recording.0, &frame) {
+        error!("failed to record frame: {}", error);
+    }
+}
+
+fn write(writer: &mut impl Write, frame: &Frame) -> anyhow::Result<()> {
+    writeln!(writer, "{}", ron::to_string(frame)?)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Steps as often as the recording did, so the replay ends up where it did.
+#[allow(clippy::needless_pass_by_value)]
+fn play(
+    playback: Res<Playback>,
+    mut timestep: ResMut<timestep::Timestep>,
+    mut join_events: EventWriter<player::JoinEvent>,
+) {
+    let Some(frame) = playback.frames.get(playback.frame) else {
+        return;
+    };
+
+    timestep.script = Some(frame.steps);
+
+    for (slot, source) in &frame.joins {
+        join_events.send(player::JoinEvent {
+            slot: *slot,
+            source: *source,
+        });
+    }
+}
+
+fn advance(mut playback: ResMut<Playback>, mut exit_events: EventWriter<AppExit>) {
+    playback.frame += 1;
+    if playback.frame >= playback.frames.len() {
+        info!("replay finished after {} frames", playback.frames.len());
+        exit_events.send(AppExit);
+    }
+}
+
+/// Logs where every player ended up, to compare a replay against its recording.
+#[allow(clippy::needless_pass_by_value)]
+fn report(
+    playback: Option<Res<Playback>>,
+    mut exit_events: EventReader<AppExit>,
+    query: Query<(&player::Player, &Transform)>,
+) {
+    let finished = playback.is_some_and(|playback| playback.frame + 1 >= playback.frames.len());
+    if exit_events.iter().next().is_none() && !finished {
+        return;
+    }
+
+    let mut players = query.iter().collect::<Vec<_>>();
+    players.sort_by_key(|(player, _)| player.slot());
+    for (player, transform) in players {
+        info!(
+            "player {} ended at {}",
+            player.slot() + 1,
+            transform.translation.truncate()
+        );
+    }
+}
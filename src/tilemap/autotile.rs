@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::tilemap::{Tile, Tilemap};
+
+/// Autotile rules for each terrain of a tileset, loaded from `*.autotile.ron` files. The first rule
+/// whose neighbours match picks the tile.
+#[derive(Deserialize, TypeUuid)]
+#[serde(transparent)]
+#[uuid = "c2b6f4d1-93a7-4e0b-8f5c-6d1e2a7b9c30"]
+pub(crate) struct Rules(HashMap<String, Vec<Rule>>);
+
+#[derive(Deserialize)]
+pub(crate) struct Rule {
+    /// Neighbours that must be painted with the same terrain.
+    #[serde(default)]
+    require: Vec<Direction>,
+    /// Neighbours that must not be painted with the same terrain.
+    #[serde(default)]
+    forbid: Vec<Direction>,
+    /// Alternatives picked by column, so patterns wider than a tile line up.
+    tiles: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub(crate) enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+/// Terrain painted onto the cells of a [`Tilemap`] on the same entity.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Autotile {
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    rules: Handle<Rules>,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    cells: HashMap<IVec2, Cell>,
+    /// Cells that currently hold a tile chosen by the rules.
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    placed: HashSet<IVec2>,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    dirty: HashSet<IVec2>,
+}
+
+struct Cell {
+    terrain: String,
+    owner: Option<Entity>,
+}
+
+#[derive(Default)]
+pub(crate) struct Loader;
+
+impl Direction {
+    const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    fn offset(self) -> IVec2 {
+        match self {
+            Direction::North => IVec2::new(0, 1),
+            Direction::NorthEast => IVec2::new(1, 1),
+            Direction::East => IVec2::new(1, 0),
+            Direction::SouthEast => IVec2::new(1, -1),
+            Direction::South => IVec2::new(0, -1),
+            Direction::SouthWest => IVec2::new(-1, -1),
+            Direction::West => IVec2::new(-1, 0),
+            Direction::NorthWest => IVec2::new(-1, 1),
+        }
+    }
+}
+
+impl Rule {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    fn tile(&self, cell: IVec2, same: impl Fn(Direction) -> bool) -> Option<usize> {
+        if !self.require.iter().all(|direction| same(*direction))
+            || self.forbid.iter().any(|direction| same(*direction))
+            || self.tiles.is_empty()
+        {
+            return None;
+        }
+
+        let column = cell.x.rem_euclid(self.tiles.len() as i32) as usize;
+        Some(self.tiles[column])
+    }
+}
+
+impl Autotile {
+    pub(crate) fn new(rules: Handle<Rules>) -> Autotile {
+        Autotile {
+            rules,
+            cells: HashMap::new(),
+            placed: HashSet::new(),
+            dirty: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn paint(&mut self, cell: IVec2, terrain: &str, owner: Option<Entity>) {
+        self.cells.insert(
+            cell,
+            Cell {
+                terrain: terrain.to_string(),
+                owner,
+            },
+        );
+        self.touch(cell);
+    }
+
+    pub(crate) fn erase(&mut self, cell: IVec2) {
+        if self.cells.remove(&cell).is_some() {
+            self.touch(cell);
+        }
+    }
+
+    pub(crate) fn remove_owned_by(&mut self, owner: Entity) {
+        let cells = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| cell.owner == Some(owner))
+            .map(|(cell, _)| *cell)
+            .collect::<Vec<_>>();
+
+        for cell in cells {
+            self.cells.remove(&cell);
+            self.touch(cell);
+        }
+    }
+
+    /// Marks a cell and its neighbours for re-evaluation.
+    fn touch(&mut self, cell: IVec2) {
+        self.dirty.insert(cell);
+        for direction in Direction::ALL {
+            self.dirty.insert(cell + direction.offset());
+        }
+    }
+
+    fn terrain(&self, cell: IVec2) -> Option<&str> {
+        self.cells.get(&cell).map(|cell| cell.terrain.as_str())
+    }
+
+    /// The tile the rules pick for a cell, if it is painted with a terrain they know.
+    fn pick(&self, rules: &Rules, cell: IVec2) -> Option<Tile> {
+        let painted = self.cells.get(&cell)?;
+        let Some(terrain) = rules.0.get(&painted.terrain) else {
+            warn!("unknown terrain {:?}", painted.terrain);
+            return None;
+        };
+
+        let same = |direction: Direction| {
+            self.terrain(cell + direction.offset()) == Some(painted.terrain.as_str())
+        };
+
+        terrain
+            .iter()
+            .find_map(|rule| rule.tile(cell, same))
+            .map(|index| Tile {
+                index,
+                owner: painted.owner,
+            })
+    }
+}
+
+/// Picks tiles for the cells whose neighbourhood changed, or every cell when the rules reload.
+#[allow(clippy::needless_pass_by_value)]
+pub(crate) fn apply(
+    rules: Res<Assets<Rules>>,
+    mut asset_events: EventReader<AssetEvent<Rules>>,
+    mut query: Query<(&mut Autotile, &mut Tilemap)>,
+) {
+    let loaded = asset_events
+        .iter()
+        .filter_map(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => Some(handle),
+            AssetEvent::Removed { .. } => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (mut autotile, mut tilemap) in query.iter_mut() {
+        if loaded.contains(&&autotile.rules) {
+            let cells = autotile.cells.keys().copied().collect::<Vec<_>>();
+            autotile.dirty.extend(cells);
+        }
+
+        if autotile.dirty.is_empty() {
+            continue;
+        }
+
+        let Some(rules) = rules.get(&autotile.rules) else {
+            continue;
+        };
+
+        for cell in std::mem::take(&mut autotile.dirty) {
+            if let Some(tile) = autotile.pick(rules, cell) {
+                tilemap.insert_cell(cell, tile);
+                autotile.placed.insert(cell);
+            } else if autotile.placed.remove(&cell) {
+                tilemap.remove_cell(cell);
+            }
+        }
+    }
+}
+
+impl AssetLoader for Loader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let rules: Rules = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(rules));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["autotile.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Rules {
+        ron::de::from_str(include_str!("../../assets/room/builder_48x48.autotile.ron")).unwrap()
+    }
+
+    fn autotile(terrain: &str, cells: &[(i32, i32)]) -> Autotile {
+        let mut autotile = Autotile::new(Handle::default());
+        for &(x, y) in cells {
+            autotile.paint(IVec2::new(x, y), terrain, None);
+        }
+        autotile
+    }
+
+    fn index(autotile: &Autotile, x: i32, y: i32) -> Option<usize> {
+        autotile
+            .pick(&rules(), IVec2::new(x, y))
+            .map(|tile| tile.index)
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let autotile = autotile("wooden floor", &[(0, 0), (0, 1)]);
+
+        // the top edge is shaded, the plank below it is not
+        assert_eq!(index(&autotile, 0, 1), Some(2542));
+        assert_eq!(index(&autotile, 0, 0), Some(2618));
+    }
+
+    #[test]
+    fn alternatives_are_picked_by_column() {
+        let autotile = autotile("wooden floor", &[(-1, 0), (0, 0), (1, 0), (2, 0), (3, 0)]);
+        let row = (-1..=3).map(|x| index(&autotile, x, 0)).collect::<Vec<_>>();
+
+        assert_eq!(
+            row,
+            [Some(2544), Some(2542), Some(2543), Some(2544), Some(2542)]
+        );
+    }
+
+    #[test]
+    fn required_and_forbidden_neighbours() {
+        let autotile = autotile("wooden wall", &[(0, 0), (1, 0), (2, 0), (1, 1)]);
+
+        // capped on the side without a neighbour, and a top piece under another row
+        assert_eq!(index(&autotile, 0, 0), Some(847));
+        assert_eq!(index(&autotile, 1, 0), Some(924));
+        assert_eq!(index(&autotile, 2, 0), Some(849));
+        assert_eq!(index(&autotile, 1, 1), Some(850));
+    }
+
+    #[test]
+    fn other_terrains_do_not_count_as_neighbours() {
+        let mut autotile = autotile("wooden floor", &[(0, 0)]);
+        autotile.paint(IVec2::new(0, 1), "wooden wall", None);
+
+        assert_eq!(index(&autotile, 0, 0), Some(2542));
+    }
+
+    #[test]
+    fn erasing_re_evaluates_the_neighbours() {
+        let mut autotile = autotile("wooden floor", &[(0, 0), (0, 1)]);
+        autotile.dirty.clear();
+
+        autotile.erase(IVec2::new(0, 1));
+
+        assert!(autotile.dirty.contains(&IVec2::new(0, 0)));
+        assert_eq!(index(&autotile, 0, 1), None);
+        assert_eq!(index(&autotile, 0, 0), Some(2542));
+    }
+
+    #[test]
+    fn unknown_terrains_pick_nothing() {
+        let autotile = autotile("lava", &[(0, 0)]);
+
+        assert_eq!(index(&autotile, 0, 0), None);
+    }
+}
@@ -0,0 +1,310 @@
+pub(crate) mod autotile;
+
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    render::{camera::Camera2d, mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::Mesh2dHandle,
+};
+
+/// Width and height of a chunk, in tiles.
+const CHUNK_SIZE: i32 = 16;
+
+/// Chunks beyond the edge of the screen that are kept spawned, so panning does not pop tiles in.
+const STREAM_MARGIN: i32 = 1;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<autotile::Rules>()
+            .init_asset_loader::<autotile::Loader>()
+            .add_system(autotile::apply)
+            .add_system(stream);
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<autotile::Autotile>()
+                .register_inspectable::<Tilemap>();
+        }
+    }
+}
+
+/// A layer of static tiles, drawn as one mesh per chunk around the camera.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Tilemap {
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    atlas: Handle<TextureAtlas>,
+    tile_size: Vec2,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    material: Option<Handle<ColorMaterial>>,
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    chunks: HashMap<IVec2, Chunk>,
+    /// What the tiles at each atlas index are tagged with in the tileset's metadata.
+    #[cfg_attr(feature = "editor", inspectable(ignore))]
+    tags: HashMap<usize, String>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Tile {
+    pub(crate) index: usize,
+    /// The entity the tile belongs to and is removed with, such as a room.
+    pub(crate) owner: Option<Entity>,
+}
+
+#[derive(Default)]
+struct Chunk {
+    tiles: HashMap<IVec2, Tile>,
+    entity: Option<Entity>,
+    mesh: Option<Handle<Mesh>>,
+    dirty: bool,
+}
+
+impl Tilemap {
+    pub(crate) fn new(atlas: Handle<TextureAtlas>, tile_size: Vec2) -> Tilemap {
+        Tilemap {
+            atlas,
+            tile_size,
+            material: None,
+            chunks: HashMap::new(),
+            tags: HashMap::new(),
+        }
+    }
+
+    /// The grid cell whose centre is nearest to `position`, relative to the tilemap.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn cell(&self, position: Vec2) -> IVec2 {
+        let cell = (position / self.tile_size).round();
+        IVec2::new(cell.x as i32, cell.y as i32)
+    }
+
+    /// Places a tile centred on `position`, relative to the tilemap, snapped to the grid.
+    pub(crate) fn insert(&mut self, position: Vec2, tile: Tile) {
+        self.insert_cell(self.cell(position), tile);
+    }
+
+    pub(crate) fn insert_cell(&mut self, cell: IVec2, tile: Tile) {
+        let (chunk, local) = split(cell);
+        let chunk = self.chunks.entry(chunk).or_default();
+        chunk.tiles.insert(local, tile);
+        chunk.dirty = true;
+    }
+
+    pub(crate) fn remove_cell(&mut self, cell: IVec2) {
+        let (chunk, local) = split(cell);
+        if let Some(chunk) = self.chunks.get_mut(&chunk) {
+            chunk.dirty |= chunk.tiles.remove(&local).is_some();
+        }
+    }
+
+    pub(crate) fn tile(&self, cell: IVec2) -> Option<Tile> {
+        let (chunk, local) = split(cell);
+        self.chunks.get(&chunk)?.tiles.get(&local).copied()
+    }
+
+    /// Tags the tiles at `index` in the atlas.
+    pub(crate) fn set_tag(&mut self, index: usize, tag: &str) {
+        self.tags.insert(index, tag.to_string());
+    }
+
+    /// The tag of the tile at `position`, relative to the tilemap, if it has one.
+    pub(crate) fn tag(&self, position: Vec2) -> Option<&str> {
+        let tile = self.tile(self.cell(position))?;
+        self.tags.get(&tile.index).map(String::as_str)
+    }
+
+    pub(crate) fn remove_owned_by(&mut self, owner: Entity) {
+        for chunk in self.chunks.values_mut() {
+            let len = chunk.tiles.len();
+            chunk.tiles.retain(|_, tile| tile.owner != Some(owner));
+            chunk.dirty |= chunk.tiles.len() != len;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn chunk_extent(&self) -> Vec2 {
+        self.tile_size * CHUNK_SIZE as f32
+    }
+}
+
+/// Splits a cell into the chunk that contains it and its position within that chunk.
+fn split(cell: IVec2) -> (IVec2, IVec2) {
+    (
+        IVec2::new(cell.x.div_euclid(CHUNK_SIZE), cell.y.div_euclid(CHUNK_SIZE)),
+        IVec2::new(cell.x.rem_euclid(CHUNK_SIZE), cell.y.rem_euclid(CHUNK_SIZE)),
+    )
+}
+
+impl Chunk {
+    #[allow(clippy::cast_precision_loss)]
+    fn mesh(&self, atlas: &TextureAtlas, tile_size: Vec2) -> Mesh {
+        let mut positions = Vec::with_capacity(self.tiles.len() * 4);
+        let mut uvs = Vec::with_capacity(self.tiles.len() * 4);
+        let mut indices = Vec::with_capacity(self.tiles.len() * 6);
+
+        for (cell, tile) in &self.tiles {
+            let Some(rect) = atlas.textures.get(tile.index) else {
+                continue;
+            };
+
+            let centre = Vec2::new(cell.x as f32, cell.y as f32) * tile_size;
+            let min = centre - tile_size / 2.0;
+            let max = centre + tile_size / 2.0;
+            let uv_min = rect.min / atlas.size;
+            let uv_max = rect.max / atlas.size;
+
+            #[allow(clippy::cast_possible_truncation)]
+            let first = positions.len() as u32;
+
+            // texture coordinates point down, world coordinates point up
+            positions.extend([
+                [min.x, min.y, 0.0],
+                [max.x, min.y, 0.0],
+                [max.x, max.y, 0.0],
+                [min.x, max.y, 0.0],
+            ]);
+            uvs.extend([
+                [uv_min.x, uv_max.y],
+                [uv_max.x, uv_max.y],
+                [uv_max.x, uv_min.y],
+                [uv_min.x, uv_min.y],
+            ]);
+            indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_NORMAL,
+            vec![[0.0, 0.0, 1.0]; positions.len()],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
+
+/// Spawns the chunks in view of a camera, despawns the rest and rebuilds any that changed.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::needless_pass_by_value
+)]
+fn stream(
+    mut commands: Commands,
+    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query_camera: Query<(&GlobalTransform, &OrthographicProjection), With<Camera2d>>,
+    mut query_tilemap: Query<(Entity, &mut Tilemap, &GlobalTransform)>,
+) {
+    for (entity, mut tilemap, transform) in query_tilemap.iter_mut() {
+        let Some(atlas) = texture_atlases.get(&tilemap.atlas) else {
+            continue;
+        };
+
+        let extent = tilemap.chunk_extent();
+        let tile_size = tilemap.tile_size;
+
+        let in_view = query_camera
+            .iter()
+            .map(|(camera, projection)| {
+                let centre = (camera.translation - transform.translation).truncate();
+                let min = Vec2::new(projection.left, projection.bottom) * projection.scale;
+                let max = Vec2::new(projection.right, projection.top) * projection.scale;
+
+                // tiles are centred on the grid, so chunks overhang their origin by half a tile
+                let min = ((centre + min + tile_size / 2.0) / extent).floor();
+                let max = ((centre + max + tile_size / 2.0) / extent).floor();
+
+                (
+                    IVec2::new(min.x as i32, min.y as i32) - STREAM_MARGIN,
+                    IVec2::new(max.x as i32, max.y as i32) + STREAM_MARGIN,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let material = tilemap
+            .material
+            .get_or_insert_with(|| materials.add(ColorMaterial::from(atlas.texture.clone())))
+            .clone();
+
+        tilemap.chunks.retain(|position, chunk| {
+            let visible = in_view
+                .iter()
+                .any(|(min, max)| position.cmpge(*min).all() && position.cmple(*max).all());
+
+            if !visible || chunk.tiles.is_empty() {
+                if let Some(entity) = chunk.entity.take() {
+                    commands.entity(entity).despawn_recursive();
+                }
+                chunk.mesh = None;
+                chunk.dirty = true;
+                return !chunk.tiles.is_empty();
+            }
+
+            if !chunk.dirty {
+                return true;
+            }
+            chunk.dirty = false;
+
+            let mesh = chunk.mesh(atlas, tile_size);
+            if let Some(handle) = &chunk.mesh {
+                if let Some(existing) = meshes.get_mut(handle) {
+                    *existing = mesh;
+                }
+            } else {
+                let handle = meshes.add(mesh);
+                let origin = position.as_vec2() * extent;
+
+                let chunk_entity = commands
+                    .spawn_bundle(ColorMesh2dBundle {
+                        mesh: Mesh2dHandle(handle.clone()),
+                        material: material.clone(),
+                        transform: Transform::from_xyz(origin.x, origin.y, 0.0),
+                        ..Default::default()
+                    })
+                    .insert(Name::new(format!("chunk ({}, {})", position.x, position.y)))
+                    .id();
+                commands.entity(entity).add_child(chunk_entity);
+
+                chunk.entity = Some(chunk_entity);
+                chunk.mesh = Some(handle);
+            }
+
+            true
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_follow_the_index_of_the_tile() {
+        let mut tilemap = Tilemap::new(Handle::default(), Vec2::splat(48.0));
+        tilemap.insert_cell(
+            IVec2::ZERO,
+            Tile {
+                index: 7,
+                owner: None,
+            },
+        );
+        tilemap.insert_cell(
+            IVec2::X,
+            Tile {
+                index: 8,
+                owner: None,
+            },
+        );
+        tilemap.set_tag(7, "carpet");
+
+        assert_eq!(tilemap.tag(Vec2::ZERO), Some("carpet"));
+        assert_eq!(tilemap.tag(Vec2::new(48.0, 0.0)), None);
+        assert_eq!(tilemap.tag(Vec2::new(96.0, 0.0)), None);
+    }
+}
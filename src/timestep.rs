@@ -0,0 +1,173 @@
+use bevy::{
+    ecs::schedule::{IntoSystemDescriptor, ShouldRun},
+    prelude::*,
+    transform::TransformSystem,
+};
+
+/// The length of a step, in seconds.
+pub(crate) const DT: f32 = 1.0 / 60.0;
+/// The most steps taken in one frame, so a slow frame cannot make the next one slower still.
+const MAX_STEPS: u32 = 5;
+
+pub(crate) struct Plugin;
+
+impl bevy::prelude::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Timestep>()
+            .add_stage_after(
+                CoreStage::Update,
+                FixedUpdate,
+                Schedule::default()
+                    .with_run_criteria(IntoSystem::into_system(should_step))
+                    .with_stage(FixedStage::Update, SystemStage::parallel())
+                    .with_stage(FixedStage::PostUpdate, SystemStage::parallel()),
+            )
+            .stage(FixedUpdate, |schedule: &mut Schedule| {
+                schedule.add_system_to_stage(FixedStage::PostUpdate, track)
+            })
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate.before(TransformSystem::TransformPropagate),
+            );
+
+        #[cfg(feature = "editor")]
+        {
+            use bevy_inspector_egui::RegisterInspectable;
+            app.register_inspectable::<Interpolated>()
+                .register_inspectable::<Interpolation>();
+        }
+    }
+}
+
+/// Runs its stages once per [`DT`] of time passed, so a frame can take no steps or several.
+///
+/// Events and input are therefore not read in here, as a step could miss them or read them twice.
+/// Systems in `Update` buffer them into components, like [`Moves`] or [`Forces`], for the steps
+/// to drain. Events sent in here are read in `Update` the next frame.
+///
+/// [`Moves`]: crate::movement::moves::Moves
+/// [`Forces`]: crate::movement::force::Forces
+#[derive(Clone, Debug, Hash, PartialEq, Eq, StageLabel)]
+pub(crate) struct FixedUpdate;
+
+/// The stages within [`FixedUpdate`]. Physics steps between them.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, StageLabel)]
+pub(crate) enum FixedStage {
+    Update,
+    /// After physics has written back where bodies ended up.
+    PostUpdate,
+}
+
+#[derive(Default)]
+pub(crate) struct Timestep {
+    /// Time not yet stepped through, in seconds.
+    accumulator: f32,
+    steps: u32,
+    /// Steps to take this frame whatever the time, as a replay does.
+    pub(crate) script: Option<u32>,
+    looping: bool,
+}
+
+/// Where the last two steps left an entity, to draw its [`Interpolated`] children in between.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Interpolation {
+    previous: Vec2,
+    current: Vec2,
+}
+
+/// A child, such as a sprite, offset to where its parent's [`Interpolation`] draws it.
+#[derive(Component)]
+#[cfg_attr(feature = "editor", derive(bevy_inspector_egui::Inspectable))]
+pub(crate) struct Interpolated;
+
+pub(crate) trait FixedSystems {
+    /// Adds a system to [`FixedStage::Update`]. It must not read events or input; see
+    /// [`FixedUpdate`].
+    fn add_fixed_system<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) -> &mut Self;
+}
+
+impl FixedSystems for App {
+    fn add_fixed_system<Params>(&mut self, system: impl IntoSystemDescriptor<Params>) -> &mut Self {
+        self.stage(FixedUpdate, |schedule: &mut Schedule| {
+            schedule.add_system_to_stage(FixedStage::Update, system)
+        })
+    }
+}
+
+impl Timestep {
+    pub(crate) fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// How far into the next step the time not yet stepped through reaches, from 0.0 to 1.0.
+    fn overstep(&self) -> f32 {
+        (self.accumulator / DT).clamp(0.0, 1.0)
+    }
+}
+
+impl Interpolation {
+    pub(crate) fn new(position: Vec2) -> Interpolation {
+        Interpolation {
+            previous: position,
+            current: position,
+        }
+    }
+}
+
+/// Called again after every step until there is no time left for another.
+#[allow(clippy::needless_pass_by_value)]
+fn should_step(time: Res<Time>, mut timestep: ResMut<Timestep>) -> ShouldRun {
+    if !timestep.looping {
+        timestep.looping = true;
+        timestep.steps = 0;
+        timestep.accumulator += time.delta_seconds();
+    }
+
+    let due = match timestep.script {
+        Some(steps) => timestep.steps < steps,
+        None => timestep.accumulator >= DT && timestep.steps < MAX_STEPS,
+    };
+
+    if !due {
+        if timestep.script.take().is_some() {
+            timestep.accumulator = 0.0;
+        }
+        // time beyond the last step allowed is dropped rather than caught up on
+        timestep.accumulator %= DT;
+        timestep.looping = false;
+        return ShouldRun::No;
+    }
+
+    timestep.accumulator = (timestep.accumulator - DT).max(0.0);
+    timestep.steps += 1;
+    ShouldRun::YesAndCheckAgain
+}
+
+fn track(mut query: Query<(&mut Interpolation, &Transform)>) {
+    for (mut interpolation, transform) in query.iter_mut() {
+        interpolation.previous = interpolation.current;
+        interpolation.current = transform.translation.truncate();
+    }
+}
+
+/// Only moves what is drawn, so the next step carries on from where the last one ended.
+#[allow(clippy::needless_pass_by_value)]
+fn interpolate(
+    timestep: Res<Timestep>,
+    query_parent: Query<&Interpolation>,
+    mut query: Query<(&Parent, &mut Transform), With<Interpolated>>,
+) {
+    let overstep = timestep.overstep();
+
+    for (parent, mut transform) in query.iter_mut() {
+        let Ok(interpolation) = query_parent.get(parent.0) else {
+            continue;
+        };
+
+        let offset =
+            interpolation.previous.lerp(interpolation.current, overstep) - interpolation.current;
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
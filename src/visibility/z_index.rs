@@ -26,8 +26,15 @@ impl ZIndex {
     }
 }
 
-fn layer(mut query: Query<(&ZIndex, &mut Transform)>) {
+/// Only entities that moved are layered, and only written to when their depth changes, so static
+/// entities are not marked as changed every frame.
+#[allow(clippy::float_cmp, clippy::type_complexity)]
+fn layer(mut query: Query<(&ZIndex, &mut Transform), Or<(Changed<ZIndex>, Changed<Transform>)>>) {
     for (z_index, mut transform) in query.iter_mut() {
-        transform.translation.z = -FRUSTUM_SCALING * transform.translation.y + z_index.0;
+        let z = -FRUSTUM_SCALING * transform.translation.y + z_index.0;
+
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
     }
 }